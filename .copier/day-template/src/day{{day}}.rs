@@ -1,20 +1,80 @@
+use crate::error::ParseError;
+use crate::parsing::integer;
+
 #[derive(Debug)]
 pub struct RowData {
+    value: usize,
+}
+
+pub fn generator(input: &str) -> Result<Vec<RowData>, ParseError> {
+    input
+        .lines()
+        .map(|line| {
+            Ok(RowData {
+                value: integer(input, line, "a number")?,
+            })
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone)]
+pub struct Params {
+    pub limit: usize,
 }
 
-pub fn generator(input: &str) -> Vec<RowData> {
-    input.lines().map(|line| {
-        RowData {
-        }
-    }).collect()
+impl Default for Params {
+    fn default() -> Self {
+        Self { limit: usize::MAX }
+    }
 }
 
 pub fn part_1(input: &[RowData]) -> usize {
-    println!("{:?}", input);
-    0
+    part_1_with(input, &Params::default())
+}
+
+pub fn part_1_with(input: &[RowData], params: &Params) -> usize {
+    input
+        .iter()
+        .map(|row| row.value)
+        .filter(|value| *value <= params.limit)
+        .sum()
 }
 
 pub fn part_2(input: &[RowData]) -> usize {
-    let _ = input;
-    0
+    part_2_with(input, &Params::default())
+}
+
+pub fn part_2_with(input: &[RowData], params: &Params) -> usize {
+    input.iter().filter(|row| row.value <= params.limit).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Replace with the example from the puzzle, and the answers below with the ones it gives.
+    const EXAMPLE: &str = "\
+1
+2
+3
+";
+
+    const EXAMPLE_PARAMS: Params = Params { limit: 2 };
+
+    #[test]
+    fn part_1_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part_1_with(&input, &EXAMPLE_PARAMS), 3);
+    }
+
+    #[test]
+    fn part_2_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part_2_with(&input, &EXAMPLE_PARAMS), 2);
+    }
+
+    #[test]
+    fn bad_input() {
+        assert!(generator("1\nx\n").is_err());
+    }
 }
@@ -0,0 +1,94 @@
+"""Registers src/dayN.rs: its module in lib.rs, its feature in Cargo.toml and its entry in the
+solution registry. Run by copier after copying the day template. Days that are already registered
+are left alone."""
+
+import re
+import sys
+from pathlib import Path
+
+
+def day_of(name):
+    return int(name[len("day"):])
+
+
+def register_module(day):
+    path = Path("src/lib.rs")
+    lines = path.read_text().splitlines(keepends=True)
+    module = f"day{day}"
+    if f"pub mod {module};\n" in lines:
+        return
+    # rustfmt keeps the modules sorted by name, so day10 comes before day2.
+    day_modules = [
+        (i, line[len("pub mod "):-len(";\n")])
+        for i, line in enumerate(lines)
+        if re.fullmatch(r"pub mod day\d+;\n", line)
+    ]
+    position = next(
+        (i - 1 for i, name in day_modules if module < name),
+        day_modules[-1][0] + 1 if day_modules else len(lines),
+    )
+    lines[position:position] = [f'#[cfg(feature = "{module}")]\n', f"pub mod {module};\n"]
+    path.write_text("".join(lines))
+
+
+def register_feature(day):
+    path = Path("Cargo.toml")
+    text = path.read_text()
+    days = {day_of(name) for name in re.findall(r"^(day\d+) = \[\]$", text, flags=re.M)}
+    if day in days:
+        return
+    days = sorted(days | {day})
+    names = [f'"day{other}"' for other in days]
+    all_days = "".join(
+        "    " + ", ".join(names[i:i + 5]) + ",\n" for i in range(0, len(names), 5)
+    )
+    features = "".join(f"day{other} = []\n" for other in days)
+    text, num_replaced = re.subn(
+        r"^all = \[\n(?:    .*\n)*\]\n(?:day\d+ = \[\]\n)*",
+        lambda _: f"all = [\n{all_days}]\n{features}",
+        text,
+        flags=re.M,
+    )
+    if num_replaced != 1:
+        sys.exit("Cargo.toml has no `all` feature followed by the day features")
+    path.write_text(text)
+
+
+def register_solution(day):
+    path = Path("src/solution.rs")
+    lines = path.read_text().splitlines(keepends=True)
+    if any(re.match(rf"    day_solution!\({day} => ", line) for line in lines):
+        return
+    try:
+        start = lines.index("pub static SOLUTIONS: &[&dyn Solution] = &[\n")
+        end = lines.index("];\n", start)
+    except ValueError:
+        sys.exit("src/solution.rs has no SOLUTIONS registry")
+    # Each entry is a #[cfg] line followed by the day_solution! line.
+    position = next(
+        (
+            i - 1
+            for i in range(start + 1, end)
+            if (entry := re.match(r"    day_solution!\((\d+) => ", lines[i]))
+            and day < int(entry[1])
+        ),
+        end,
+    )
+    lines[position:position] = [
+        f'    #[cfg(feature = "day{day}")]\n',
+        f"    day_solution!({day} => day{day}),\n",
+    ]
+    path.write_text("".join(lines))
+
+
+def main():
+    day = int(sys.argv[1])
+    if not Path(f"src/day{day}.rs").is_file():
+        sys.exit(f"src/day{day}.rs does not exist")
+    register_module(day)
+    register_feature(day)
+    register_solution(day)
+
+
+if __name__ == "__main__":
+    main()
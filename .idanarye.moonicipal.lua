@@ -61,23 +61,11 @@ function T:add_day()
         default = vim.fn.strftime('%d'),
         prompt = 'Day number: ',
     }) or moonicipal.abort('No number selected')
-    vim.cmd('!copier -fd day=' .. day .. ' copy .copier/day-template .')
-    do
-        local lines = vim.fn.readfile('src/lib.rs')
-        table.insert(lines, ('pub mod day%s;'):format(day))
-        vim.fn.writefile(lines, 'src/lib.rs')
-    end
-
-    do
-        local lines = vim.fn.readfile('src/main.rs')
-        -- dump(lines)
-        if lines[#lines] ~= '}' then
-            error('Malformed main.rs')
-        end
-        table.insert(lines, #lines, ('    day%s : generator => part_1, part_2;'):format(day))
-        dump(lines)
-        vim.fn.writefile(lines, 'src/main.rs')
+    if vim.fn.filereadable(('src/day%s.rs'):format(day)) == 1 then
+        moonicipal.abort(('Day %s already exists'):format(day))
     end
+    -- The template's task registers the day in lib.rs, Cargo.toml and solution.rs.
+    vim.cmd('!copier copy --trust --defaults --data day=' .. day .. ' .copier/day-template .')
     vim.cmd.checktime()
 end
 
@@ -105,3 +93,8 @@ end
 function T:clippy()
     vim.cmd'Erun! cargo clippy -q'
 end
+
+-- The helpers must still build when no day is enabled.
+function T:clippy_no_days()
+    vim.cmd'Erun! cargo clippy -q --all-targets --no-default-features -- -D warnings'
+end
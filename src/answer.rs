@@ -0,0 +1,92 @@
+use std::convert::Infallible;
+use std::fmt::Display;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Answer {
+    Number(i128),
+    Text(String),
+}
+
+macro_rules! impl_from_number {
+    ($($typ:ty),*) => {
+        $(
+            impl From<$typ> for Answer {
+                fn from(number: $typ) -> Self {
+                    Answer::Number(number as i128)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_number!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize, i128);
+
+impl Answer {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Answer::Number(_) => "number",
+            Answer::Text(_) => "text",
+        }
+    }
+}
+
+/// Text that reads as an integer becomes a [`Answer::Number`], so that an answer read back from a
+/// file compares equal to the one the solver produced.
+impl From<String> for Answer {
+    fn from(text: String) -> Self {
+        if let Ok(number) = text.parse() {
+            Answer::Number(number)
+        } else {
+            Answer::Text(text)
+        }
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(text: &str) -> Self {
+        text.to_owned().into()
+    }
+}
+
+impl FromStr for Answer {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(s.into())
+    }
+}
+
+impl Display for Answer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Answer::Number(number) => write!(f, "{}", number),
+            Answer::Text(text) => f.write_str(text),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conversions() {
+        assert_eq!(Answer::from(42usize), Answer::Number(42));
+        assert_eq!(Answer::from(-3isize), Answer::Number(-3));
+        assert_eq!(Answer::from("CMZ"), Answer::Text("CMZ".to_owned()));
+        assert_eq!(Answer::from("-17".to_owned()), Answer::Number(-17));
+        assert_eq!("2=-1=0".parse(), Ok(Answer::from("2=-1=0")));
+    }
+
+    #[test]
+    fn display_round_trip() {
+        for answer in [
+            Answer::Number(1514285714288),
+            Answer::Text("2=-1=0".to_owned()),
+            Answer::Text("\n##..\n.##.".to_owned()),
+        ] {
+            assert_eq!(answer.to_string().parse(), Ok(answer));
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::check::ExpectedAnswers;
+
+pub const ANSWERS_EXTENSION: &str = "answers";
+
+pub struct BatchInput {
+    pub path: PathBuf,
+    pub data: String,
+    /// Loaded from a file next to the input, with the same name but the `answers` extension.
+    pub expected: Option<ExpectedAnswers>,
+}
+
+impl BatchInput {
+    pub fn file_name(&self) -> String {
+        self.path
+            .file_name()
+            .map_or_else(String::new, |name| name.to_string_lossy().into_owned())
+    }
+}
+
+/// Reads all the input files in `dir`, sorted by name. Answer files and hidden files are skipped.
+pub fn load_inputs(dir: impl AsRef<Path>) -> io::Result<Vec<BatchInput>> {
+    let mut paths = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let is_hidden = entry.file_name().to_string_lossy().starts_with('.');
+        let is_answers = path.extension().is_some_and(|ext| ext == ANSWERS_EXTENSION);
+        if entry.file_type()?.is_file() && !is_hidden && !is_answers {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    paths
+        .into_iter()
+        .map(|path| {
+            let data = std::fs::read_to_string(&path)?;
+            let answers_path = path.with_extension(ANSWERS_EXTENSION);
+            let expected = if answers_path.exists() {
+                Some(ExpectedAnswers::load(&answers_path)?)
+            } else {
+                None
+            };
+            Ok(BatchInput {
+                path,
+                data,
+                expected,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::Part;
+
+    #[test]
+    fn load_directory() {
+        let dir = std::env::temp_dir().join(format!("aoc-2022-batch-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("bob.txt"), "2\n").unwrap();
+        std::fs::write(dir.join("alice.txt"), "1\n").unwrap();
+        std::fs::write(dir.join("alice.answers"), "part_1: 1\n").unwrap();
+        std::fs::write(dir.join(".hidden"), "").unwrap();
+
+        let inputs = load_inputs(&dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+        let inputs = inputs.unwrap();
+
+        assert_eq!(
+            inputs.iter().map(BatchInput::file_name).collect::<Vec<_>>(),
+            ["alice.txt", "bob.txt"]
+        );
+        assert_eq!(inputs[0].data, "1\n");
+        assert_eq!(
+            inputs[0].expected.as_ref().unwrap().get(Part::One),
+            Some(&1.into())
+        );
+        assert!(inputs[1].expected.is_none());
+    }
+}
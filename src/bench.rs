@@ -0,0 +1,290 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::io;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Map, Value};
+
+use crate::error::ParseError;
+use crate::solution::{Part, Solution};
+
+pub const DEFAULT_BASELINE_PATH: &str = "bench/baseline.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Stage {
+    Generator,
+    Part(Part),
+}
+
+impl Stage {
+    pub const ALL: [Stage; 3] = [
+        Stage::Generator,
+        Stage::Part(Part::One),
+        Stage::Part(Part::Two),
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Stage::Generator => "generator",
+            Stage::Part(part) => part.name(),
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|stage| stage.name() == name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timing {
+    pub median: Duration,
+    pub min: Duration,
+    pub max: Duration,
+}
+
+impl Timing {
+    pub fn from_samples(mut samples: Vec<Duration>) -> Self {
+        assert!(!samples.is_empty(), "no samples to summarize");
+        samples.sort();
+        let mid = samples.len() / 2;
+        let median = if samples.len().is_multiple_of(2) {
+            (samples[mid - 1] + samples[mid]) / 2
+        } else {
+            samples[mid]
+        };
+        Self {
+            median,
+            min: samples[0],
+            max: samples[samples.len() - 1],
+        }
+    }
+
+    fn to_json(self) -> Value {
+        let nanos = |duration: Duration| duration.as_nanos() as u64;
+        json!({
+            "median_ns": nanos(self.median),
+            "min_ns": nanos(self.min),
+            "max_ns": nanos(self.max),
+        })
+    }
+
+    fn from_json(json: &Value) -> Option<Self> {
+        let nanos = |key: &str| Some(Duration::from_nanos(json.get(key)?.as_f64()? as u64));
+        Some(Self {
+            median: nanos("median_ns")?,
+            min: nanos("min_ns")?,
+            max: nanos("max_ns")?,
+        })
+    }
+}
+
+impl Display for Timing {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "median {:.2?}, min {:.2?}, max {:.2?}",
+            self.median, self.min, self.max
+        )
+    }
+}
+
+fn time_iterations(iterations: usize, mut f: impl FnMut()) -> Timing {
+    Timing::from_samples(
+        (0..iterations)
+            .map(|_| {
+                let start = Instant::now();
+                f();
+                start.elapsed()
+            })
+            .collect(),
+    )
+}
+
+/// Runs each stage of the solution `iterations` times. The parts all run on the input parsed by
+/// an extra generator run.
+pub fn bench_solution(
+    solution: &dyn Solution,
+    data: &str,
+    iterations: usize,
+) -> Result<Vec<(Stage, Timing)>, ParseError> {
+    assert!(0 < iterations, "must run at least one iteration");
+    let input = solution.parse(data)?;
+    let mut result = vec![(
+        Stage::Generator,
+        time_iterations(iterations, || {
+            solution.parse(data).unwrap();
+        }),
+    )];
+    for part in Part::ALL {
+        result.push((
+            Stage::Part(part),
+            time_iterations(iterations, || {
+                // Failing to find an answer takes time too.
+                let _ = solution.run_part(part, &input);
+            }),
+        ));
+    }
+    Ok(result)
+}
+
+/// The entries of a JSON object, or none for anything else.
+fn entries(value: &Value) -> impl Iterator<Item = (&String, &Value)> {
+    value.as_object().into_iter().flatten()
+}
+
+/// Timings keyed by solution name and stage.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Baseline(BTreeMap<(String, Stage), Timing>);
+
+impl Baseline {
+    pub fn get(&self, solution_name: &str, stage: Stage) -> Option<&Timing> {
+        self.0.get(&(solution_name.to_owned(), stage))
+    }
+
+    pub fn set(&mut self, solution_name: &str, stage: Stage, timing: Timing) {
+        self.0.insert((solution_name.to_owned(), stage), timing);
+    }
+
+    pub fn to_json(&self) -> Value {
+        let mut days = Map::new();
+        for ((solution_name, stage), timing) in self.0.iter() {
+            days.entry(solution_name.clone())
+                .or_insert_with(|| json!({}))[stage.name()] = timing.to_json();
+        }
+        days.into()
+    }
+
+    pub fn from_json(input: &str) -> Result<Self, ParseError> {
+        let json = serde_json::from_str::<Value>(input).map_err(|err| {
+            // The error only knows where it happened, so point at that place in the input.
+            let line = input.split('\n').nth(err.line().saturating_sub(1));
+            let at = line.and_then(|line| line.get(err.column().saturating_sub(1)..));
+            ParseError::new(input, at.unwrap_or(&input[input.len()..]), "valid JSON")
+        })?;
+        let mut result = Self::default();
+        for (solution_name, stages) in entries(&json) {
+            for (stage_name, timing) in entries(stages) {
+                let stage = Stage::from_name(stage_name).ok_or_else(|| {
+                    ParseError::at_end(input, format!("a known stage instead of {:?}", stage_name))
+                })?;
+                let timing = Timing::from_json(timing).ok_or_else(|| {
+                    ParseError::at_end(
+                        input,
+                        format!("timings for {}.{}", solution_name, stage_name),
+                    )
+                })?;
+                result.set(solution_name, stage, timing);
+            }
+        }
+        Ok(result)
+    }
+
+    /// Returns an empty baseline if the file does not exist.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Self::from_json(&text)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err),
+        }
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, format!("{:#}\n", self.to_json()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Comparison {
+    NoBaseline,
+    /// `ratio` is the current median divided by the baseline's.
+    WithinThreshold {
+        ratio: f64,
+    },
+    Regression {
+        ratio: f64,
+    },
+}
+
+/// `threshold` is the fraction the median may grow by, e.g. `0.1` for 10%.
+pub fn compare(baseline: Option<&Timing>, current: &Timing, threshold: f64) -> Comparison {
+    let Some(baseline) = baseline else {
+        return Comparison::NoBaseline;
+    };
+    let ratio = current.median.as_secs_f64() / baseline.median.as_secs_f64().max(f64::MIN_POSITIVE);
+    if 1.0 + threshold < ratio {
+        Comparison::Regression { ratio }
+    } else {
+        Comparison::WithinThreshold { ratio }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn timing_from_samples() {
+        let timing = Timing::from_samples(vec![ms(5), ms(1), ms(3)]);
+        assert_eq!(
+            (timing.median, timing.min, timing.max),
+            (ms(3), ms(1), ms(5))
+        );
+        let timing = Timing::from_samples(vec![ms(4), ms(1), ms(2), ms(9)]);
+        assert_eq!(timing.median, ms(3));
+    }
+
+    #[test]
+    fn baseline_round_trip() {
+        let mut baseline = Baseline::default();
+        let timing = Timing::from_samples(vec![ms(1), ms(2), ms(4)]);
+        baseline.set("day15", Stage::Part(Part::Two), timing);
+        baseline.set("day15", Stage::Generator, timing);
+        baseline.set("day9", Stage::Part(Part::One), timing);
+        let json = format!("{:#}", baseline.to_json());
+        assert_eq!(Baseline::from_json(&json), Ok(baseline));
+    }
+
+    #[test]
+    fn malformed_baseline() {
+        let err = Baseline::from_json("{\n  \"day1\": {\"part1\": x}\n}\n").unwrap_err();
+        assert_eq!((err.line, err.column, err.text.as_str()), (2, 21, "x}"));
+        let err = Baseline::from_json(r#"{"day1": {"part3": {}}}"#).unwrap_err();
+        assert!(err.expected.contains("part3"), "{}", err);
+    }
+
+    #[test]
+    fn regressions() {
+        let baseline = Timing::from_samples(vec![ms(100)]);
+        assert_eq!(compare(None, &baseline, 0.1), Comparison::NoBaseline);
+        assert!(matches!(
+            compare(Some(&baseline), &Timing::from_samples(vec![ms(105)]), 0.1),
+            Comparison::WithinThreshold { ratio } if (ratio - 1.05).abs() < 1e-9
+        ));
+        assert!(matches!(
+            compare(Some(&baseline), &Timing::from_samples(vec![ms(150)]), 0.1),
+            Comparison::Regression { ratio } if (ratio - 1.5).abs() < 1e-9
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "day1")]
+    fn bench_day() {
+        let solution = crate::solution::get(1).unwrap();
+        let timings = bench_solution(solution, "1\n2\n\n3\n", 3).unwrap();
+        assert_eq!(
+            timings.iter().map(|(stage, _)| *stage).collect::<Vec<_>>(),
+            Stage::ALL
+        );
+        assert!(bench_solution(solution, "x\n", 3).is_err());
+    }
+}
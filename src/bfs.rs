@@ -1,139 +1,977 @@
-use std::collections::VecDeque;
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, VecDeque};
 use std::hash::Hash;
-use std::ops::Add;
+use std::marker::PhantomData;
 
-use hashbrown::HashMap;
+use hashbrown::{HashMap, HashSet};
+
+use crate::stats;
+
+/// What a search adds up along a path.
+pub trait Cost: Clone + Ord {
+    fn plus(&self, other: &Self) -> Self;
+}
+
+macro_rules! impl_cost {
+    ($($type:ty)*) => {
+        $(
+            impl Cost for $type {
+                fn plus(&self, other: &Self) -> Self {
+                    self + other
+                }
+            }
+        )*
+    };
+}
+
+impl_cost!(u8 u16 u32 u64 usize i8 i16 i32 i64 isize);
+
+/// For searches that only care about which nodes they reach.
+impl Cost for () {
+    fn plus(&self, _: &Self) -> Self {}
+}
+
+/// Where a search keeps what it learned about the nodes it reached.
+pub trait Storage<K, C> {
+    /// The cost of the cheapest path found to `key`, if any was.
+    fn cost(&self, key: &K) -> Option<&C>;
+
+    fn insert(&mut self, key: K, parent: Option<K>, cost: C);
+
+    fn all_known(&self) -> impl Iterator<Item = K> + '_;
+}
+
+/// A [`Storage`] that remembers how each node was reached.
+pub trait ParentStorage<K, C>: Storage<K, C> {
+    /// `None` for the roots.
+    fn parent(&self, key: &K) -> Option<&K>;
+}
 
 struct BfsCell<P, C> {
     parent: Option<P>,
     cost: C,
 }
 
-pub struct LinearBfs<C> {
+/// For nodes that are indices smaller than a known size.
+pub struct DenseStorage<C> {
     cells: Vec<Option<BfsCell<usize, C>>>,
-    consider: VecDeque<usize>,
 }
 
-impl<C> LinearBfs<C>
-where
-    for<'a> &'a C: Add<&'a C, Output = C>,
-    C: PartialOrd<C>,
-{
+impl<C> DenseStorage<C> {
     pub fn new(size: usize) -> Self {
         Self {
             cells: std::iter::repeat_with(|| None).take(size).collect(),
-            consider: VecDeque::new(),
         }
     }
+}
+
+impl<C> Storage<usize, C> for DenseStorage<C> {
+    fn cost(&self, key: &usize) -> Option<&C> {
+        self.cells[*key].as_ref().map(|cell| &cell.cost)
+    }
 
-    pub fn cost(&self, key: usize) -> Option<&C> {
-        self.cells[key].as_ref().map(|cell| &cell.cost)
+    fn insert(&mut self, key: usize, parent: Option<usize>, cost: C) {
+        self.cells[key] = Some(BfsCell { parent, cost });
     }
 
-    pub fn add_root(&mut self, key: usize, cost: C) {
-        self.cells[key] = Some(BfsCell { parent: None, cost });
-        self.consider.push_back(key)
+    fn all_known(&self) -> impl Iterator<Item = usize> + '_ {
+        self.cells
+            .iter()
+            .enumerate()
+            .filter_map(|(key, cell)| cell.as_ref().map(|_| key))
     }
+}
 
-    pub fn consider_next(&mut self) -> Option<usize> {
-        self.consider.pop_front()
+impl<C> ParentStorage<usize, C> for DenseStorage<C> {
+    fn parent(&self, key: &usize) -> Option<&usize> {
+        self.cells[*key].as_ref().unwrap().parent.as_ref()
     }
+}
 
-    pub fn add_edge(&mut self, parent: usize, key: usize, additional_cost: C) -> bool {
-        let new_cost = self.cost(parent).unwrap() + &additional_cost;
-        if let Some(existing_cell) = &self.cells[key] {
-            // TODO: this may fail because I'm using VecDeque instead of a BinaryHeap
-            if new_cost < existing_cell.cost {
-                assert!(self.consider.contains(&key), "out of order");
-            } else {
-                return false;
-            }
+/// For any hashable nodes.
+pub struct HashMapStorage<K, C> {
+    cells: HashMap<K, BfsCell<K, C>>,
+}
+
+impl<K, C> Default for HashMapStorage<K, C> {
+    fn default() -> Self {
+        Self {
+            cells: HashMap::new(),
+        }
+    }
+}
+
+impl<K: Eq + Hash + Clone, C> Storage<K, C> for HashMapStorage<K, C> {
+    fn cost(&self, key: &K) -> Option<&C> {
+        self.cells.get(key).map(|cell| &cell.cost)
+    }
+
+    fn insert(&mut self, key: K, parent: Option<K>, cost: C) {
+        self.cells.insert(key, BfsCell { parent, cost });
+    }
+
+    fn all_known(&self) -> impl Iterator<Item = K> + '_ {
+        self.cells.keys().cloned()
+    }
+}
+
+impl<K: Eq + Hash + Clone, C> ParentStorage<K, C> for HashMapStorage<K, C> {
+    fn parent(&self, key: &K) -> Option<&K> {
+        self.cells.get(key).unwrap().parent.as_ref()
+    }
+}
+
+/// Only remembers which of the indices smaller than a known size were reached - one bit each.
+pub struct BitSetStorage {
+    words: Vec<u64>,
+}
+
+impl BitSetStorage {
+    pub fn new(size: usize) -> Self {
+        Self {
+            words: vec![0; size.div_ceil(64)],
+        }
+    }
+}
+
+impl Storage<usize, ()> for BitSetStorage {
+    fn cost(&self, key: &usize) -> Option<&()> {
+        (self.words[key / 64] & (1 << (key % 64)) != 0).then_some(&())
+    }
+
+    fn insert(&mut self, key: usize, _parent: Option<usize>, _cost: ()) {
+        self.words[key / 64] |= 1 << (key % 64);
+    }
+
+    fn all_known(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(i, &word)| {
+            (0..64)
+                .filter(move |bit| word & (1 << bit) != 0)
+                .map(move |bit| i * 64 + bit)
+        })
+    }
+}
+
+/// The nodes a search has reached but not expanded yet, and the order to expand them in.
+pub trait Frontier<K, C> {
+    /// `improved` is set when `key` was already reached before, through a more expensive path.
+    fn push(&mut self, key: K, cost: C, improved: bool);
+
+    /// The next node to expand, with the cost it was pushed with.
+    fn pop(&mut self) -> Option<(K, C)>;
+
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Expands nodes in the order they were reached, which is only the cost order when all the edges
+/// cost the same.
+pub struct Fifo<K, C> {
+    queue: VecDeque<(K, C)>,
+}
+
+impl<K, C> Default for Fifo<K, C> {
+    fn default() -> Self {
+        Self {
+            queue: VecDeque::new(),
         }
-        self.consider.push_back(key);
-        self.cells[key] = Some(BfsCell {
-            parent: Some(parent),
-            cost: new_cost,
+    }
+}
+
+impl<K: PartialEq, C> Frontier<K, C> for Fifo<K, C> {
+    fn push(&mut self, key: K, cost: C, improved: bool) {
+        if improved {
+            assert!(
+                self.queue.iter().any(|(queued, _)| *queued == key),
+                "out of order - edges of different costs need a Dijkstra"
+            );
+        }
+        self.queue.push_back((key, cost));
+    }
+
+    fn pop(&mut self) -> Option<(K, C)> {
+        self.queue.pop_front()
+    }
+
+    fn len(&self) -> usize {
+        self.queue.len()
+    }
+}
+
+/// A frontier entry. [`BinaryHeap`] pops the greatest entry first, so a lower priority compares
+/// as greater.
+struct Candidate<K, C> {
+    key: K,
+    priority: C,
+}
+
+impl<K, C: Ord> PartialEq for Candidate<K, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority
+    }
+}
+
+impl<K, C: Ord> Eq for Candidate<K, C> {}
+
+impl<K, C: Ord> PartialOrd for Candidate<K, C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<K, C: Ord> Ord for Candidate<K, C> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.priority.cmp(&self.priority)
+    }
+}
+
+/// Always expands the cheapest node next, so edge costs may differ.
+///
+/// Finding a cheaper path to a node that is already in the frontier pushes it again instead of
+/// updating it, and the search skips the outdated entry when it comes up.
+pub struct Cheapest<K, C> {
+    heap: BinaryHeap<Candidate<K, C>>,
+}
+
+impl<K, C: Ord> Default for Cheapest<K, C> {
+    fn default() -> Self {
+        Self {
+            heap: BinaryHeap::new(),
+        }
+    }
+}
+
+impl<K, C: Ord + Clone> Frontier<K, C> for Cheapest<K, C> {
+    fn push(&mut self, key: K, cost: C, _improved: bool) {
+        self.heap.push(Candidate {
+            key,
+            priority: cost,
         });
-        true
     }
 
-    pub fn path_to(&self, key: usize) -> Vec<usize> {
-        let mut result = Vec::new();
-        let mut currentlly_at = Some(key);
-        while let Some(at) = currentlly_at {
-            result.push(at);
-            currentlly_at = self.cells[at].as_ref().unwrap().parent;
+    fn pop(&mut self) -> Option<(K, C)> {
+        self.heap
+            .pop()
+            .map(|Candidate { key, priority }| (key, priority))
+    }
+
+    fn len(&self) -> usize {
+        self.heap.len()
+    }
+}
+
+/// The estimated total cost through a node, and then the cost to reach it. Among equal estimates,
+/// the nodes further along are expanded first.
+type Estimate<C> = (C, Reverse<C>);
+
+/// Like [`Cheapest`], but prefers nodes that `heuristic` estimates to be closer to the goal.
+///
+/// The first time the goal is considered its path is the cheapest one only if the heuristic never
+/// overestimates, and never drops by more than an edge's cost along that edge. The Manhattan
+/// distance to the goal does that when each step costs at least 1.
+pub struct Estimated<K, C, H> {
+    heap: BinaryHeap<Candidate<K, Estimate<C>>>,
+    heuristic: H,
+}
+
+impl<K, C: Ord, H> Estimated<K, C, H> {
+    pub fn new(heuristic: H) -> Self {
+        Self {
+            heap: BinaryHeap::new(),
+            heuristic,
         }
-        result.reverse();
-        result
     }
 }
-pub struct HashMapBfs<K, C> {
-    cells: HashMap<K, BfsCell<K, C>>,
-    consider: VecDeque<K>,
+
+impl<K, C: Cost, H: Fn(&K) -> C> Frontier<K, C> for Estimated<K, C, H> {
+    fn push(&mut self, key: K, cost: C, _improved: bool) {
+        let priority = (cost.plus(&(self.heuristic)(&key)), Reverse(cost));
+        self.heap.push(Candidate { key, priority });
+    }
+
+    fn pop(&mut self) -> Option<(K, C)> {
+        self.heap.pop().map(
+            |Candidate {
+                 key,
+                 priority: (_, Reverse(cost)),
+             }| (key, cost),
+        )
+    }
+
+    fn len(&self) -> usize {
+        self.heap.len()
+    }
 }
 
-impl<K, C> HashMapBfs<K, C>
-where
-    K: PartialEq + Eq + Hash + Clone,
-    for<'a> &'a C: Add<&'a C, Output = C>,
-    C: PartialOrd<C>,
-{
-    pub fn new() -> Self {
+/// A search from some roots, that expands the nodes in the order its [`Frontier`] picks and keeps
+/// the cheapest path to each in its [`Storage`].
+pub struct Search<K, C, S, F> {
+    storage: S,
+    frontier: F,
+    expanded: usize,
+    phantom: PhantomData<(K, C)>,
+}
+
+pub type LinearBfs<C> = Search<usize, C, DenseStorage<C>, Fifo<usize, C>>;
+pub type HashMapBfs<K, C> = Search<K, C, HashMapStorage<K, C>, Fifo<K, C>>;
+/// A BFS that only finds which nodes are reachable.
+pub type BitSetBfs = Search<usize, (), BitSetStorage, Fifo<usize, ()>>;
+pub type LinearDijkstra<C> = Search<usize, C, DenseStorage<C>, Cheapest<usize, C>>;
+pub type HashMapDijkstra<K, C> = Search<K, C, HashMapStorage<K, C>, Cheapest<K, C>>;
+pub type LinearAStar<C, H> = Search<usize, C, DenseStorage<C>, Estimated<usize, C, H>>;
+pub type HashMapAStar<K, C, H> = Search<K, C, HashMapStorage<K, C>, Estimated<K, C, H>>;
+pub type LinearBidirectionalBfs = BidirectionalBfs<DenseStorage<usize>>;
+pub type HashMapBidirectionalBfs<K> = BidirectionalBfs<HashMapStorage<K, usize>>;
+
+impl<K, C, S: Default, F: Default> Default for Search<K, C, S, F> {
+    fn default() -> Self {
+        Self::with(S::default(), F::default())
+    }
+}
+
+impl<K, C, S, F> Search<K, C, S, F> {
+    pub fn with(storage: S, frontier: F) -> Self {
         Self {
-            cells: HashMap::new(),
-            consider: VecDeque::new(),
+            storage,
+            frontier,
+            expanded: 0,
+            phantom: PhantomData,
         }
     }
+}
 
+impl<C: Cost, F: Frontier<usize, C> + Default> Search<usize, C, DenseStorage<C>, F> {
+    pub fn new(size: usize) -> Self {
+        Self::with(DenseStorage::new(size), F::default())
+    }
+}
+
+impl<K: Eq + Hash + Clone, C: Cost, F: Frontier<K, C> + Default>
+    Search<K, C, HashMapStorage<K, C>, F>
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<F: Frontier<usize, ()> + Default> Search<usize, (), BitSetStorage, F> {
+    pub fn new(size: usize) -> Self {
+        Self::with(BitSetStorage::new(size), F::default())
+    }
+}
+
+impl<C: Cost, H: Fn(&usize) -> C> LinearAStar<C, H> {
+    pub fn new(size: usize, heuristic: H) -> Self {
+        Self::with(DenseStorage::new(size), Estimated::new(heuristic))
+    }
+}
+
+impl<K: Eq + Hash + Clone, C: Cost, H: Fn(&K) -> C> HashMapAStar<K, C, H> {
+    pub fn new(heuristic: H) -> Self {
+        Self::with(HashMapStorage::default(), Estimated::new(heuristic))
+    }
+}
+
+impl<K, C, S, F> Search<K, C, S, F>
+where
+    K: Clone,
+    C: Cost,
+    S: Storage<K, C>,
+    F: Frontier<K, C>,
+{
     pub fn cost(&self, key: &K) -> Option<&C> {
-        self.cells.get(key).map(|cell| &cell.cost)
+        self.storage.cost(key)
+    }
+
+    /// How many nodes [`Search::consider_next`] returned so far.
+    pub fn expanded(&self) -> usize {
+        self.expanded
     }
 
     pub fn add_root(&mut self, key: K, cost: C) {
-        self.cells
-            .insert(key.clone(), BfsCell { parent: None, cost });
-        self.consider.push_back(key)
+        self.frontier.push(key.clone(), cost.clone(), false);
+        stats::queue_size(self.frontier.len());
+        self.storage.insert(key, None, cost);
     }
 
     pub fn consider_next(&mut self) -> Option<K> {
-        self.consider.pop_front()
+        while let Some((key, cost)) = self.frontier.pop() {
+            // A cheaper path was found after this entry was pushed.
+            if self.storage.cost(&key) != Some(&cost) {
+                continue;
+            }
+            stats::node_expanded();
+            self.expanded += 1;
+            return Some(key);
+        }
+        None
     }
 
     pub fn add_edge(&mut self, parent: K, key: K, additional_cost: C) -> bool {
-        let new_cost = self.cost(&parent).unwrap() + &additional_cost;
-        if let Some(existing_cell) = self.cells.get(&key) {
-            // TODO: this may fail because I'm using VecDeque instead of a BinaryHeap
-            if new_cost < existing_cell.cost {
-                assert!(self.consider.contains(&key), "out of order");
-            } else {
+        let new_cost = self.cost(&parent).unwrap().plus(&additional_cost);
+        let improved = match self.storage.cost(&key) {
+            Some(cost) if *cost <= new_cost => {
+                stats::memo_hit();
                 return false;
             }
-        }
-        self.consider.push_back(key.clone());
-        self.cells.insert(
-            key,
-            BfsCell {
-                parent: Some(parent),
-                cost: new_cost,
-            },
-        );
+            Some(_) => true,
+            None => false,
+        };
+        self.frontier.push(key.clone(), new_cost.clone(), improved);
+        stats::queue_size(self.frontier.len());
+        self.storage.insert(key, Some(parent), new_cost);
         true
     }
 
-    #[allow(unused)]
+    pub fn all_known(&self) -> impl Iterator<Item = K> + '_ {
+        self.storage.all_known()
+    }
+
+    /// Searches from `roots`, finding the nodes that can be reached from each node and the cost of
+    /// getting to them with `successors`.
+    pub fn walk<N, I>(
+        mut self,
+        roots: impl IntoIterator<Item = (K, C)>,
+        successors: N,
+    ) -> Walk<K, C, S, F, N>
+    where
+        N: FnMut(&K) -> I,
+        I: IntoIterator<Item = (K, C)>,
+    {
+        for (key, cost) in roots {
+            self.add_root(key, cost);
+        }
+        Walk {
+            search: self,
+            successors,
+            pending: None,
+        }
+    }
+}
+
+impl<K, C, S, F> Search<K, C, S, F>
+where
+    K: Clone,
+    S: ParentStorage<K, C>,
+{
     pub fn path_to(&self, key: &K) -> Vec<K> {
         let mut result = Vec::new();
-        let mut currentlly_at = Some(key);
-        while let Some(at) = currentlly_at {
+        let mut walk_back = Some(key);
+        while let Some(at) = walk_back {
             result.push(at.clone());
-            currentlly_at = self.cells.get(at).unwrap().parent.as_ref();
+            walk_back = self.storage.parent(at);
         }
         result.reverse();
         result
     }
+}
+
+/// A [`Search`] that gets the edges from a closure. See [`Search::walk`].
+///
+/// It is an iterator of each node, its cost and its parent in visit order when the storage keeps
+/// the parents.
+pub struct Walk<K, C, S, F, N> {
+    search: Search<K, C, S, F>,
+    successors: N,
+    /// The node returned last. It is expanded on the next step, so the search can be looked at
+    /// before it is.
+    pending: Option<K>,
+}
+
+impl<K, C, S, F, N, I> Walk<K, C, S, F, N>
+where
+    K: Clone,
+    C: Cost,
+    S: Storage<K, C>,
+    F: Frontier<K, C>,
+    N: FnMut(&K) -> I,
+    I: IntoIterator<Item = (K, C)>,
+{
+    pub fn search(&self) -> &Search<K, C, S, F> {
+        &self.search
+    }
+
+    /// The next node in visit order.
+    pub fn next_node(&mut self) -> Option<K> {
+        if let Some(key) = self.pending.take() {
+            for (successor, cost) in (self.successors)(&key) {
+                self.search.add_edge(key.clone(), successor, cost);
+            }
+        }
+        let key = self.search.consider_next()?;
+        self.pending = Some(key.clone());
+        Some(key)
+    }
+
+    /// The first node visited that satisfies `goal`. Its successors are not expanded.
+    pub fn find_first(&mut self, mut goal: impl FnMut(&K) -> bool) -> Option<K> {
+        while let Some(key) = self.next_node() {
+            if goal(&key) {
+                return Some(key);
+            }
+        }
+        None
+    }
+
+    /// Visits everything that can be reached.
+    pub fn run(mut self) -> Search<K, C, S, F> {
+        while self.next_node().is_some() {}
+        self.search
+    }
+
+    pub fn reachable_set(self) -> HashSet<K>
+    where
+        K: Eq + Hash,
+    {
+        self.run().all_known().collect()
+    }
+}
+
+impl<K, C, S, F, N, I> Iterator for Walk<K, C, S, F, N>
+where
+    K: Clone,
+    C: Cost,
+    S: ParentStorage<K, C>,
+    F: Frontier<K, C>,
+    N: FnMut(&K) -> I,
+    I: IntoIterator<Item = (K, C)>,
+{
+    type Item = (K, C, Option<K>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let key = self.next_node()?;
+        let cost = self.search.cost(&key).unwrap().clone();
+        let parent = self.search.storage.parent(&key).cloned();
+        Some((key, cost, parent))
+    }
+}
+
+/// A BFS from both ends of a point to point query at once, which stops where the two meet. Each
+/// side only has to get about half way, so it explores far less than a BFS from one end.
+pub struct BidirectionalBfs<S> {
+    forward: S,
+    backward: S,
+}
+
+impl<S> BidirectionalBfs<S> {
+    pub fn with(forward: S, backward: S) -> Self {
+        Self { forward, backward }
+    }
+}
+
+impl BidirectionalBfs<DenseStorage<usize>> {
+    pub fn new(size: usize) -> Self {
+        Self::with(DenseStorage::new(size), DenseStorage::new(size))
+    }
+}
+
+impl<K> Default for BidirectionalBfs<HashMapStorage<K, usize>> {
+    fn default() -> Self {
+        Self::with(HashMapStorage::default(), HashMapStorage::default())
+    }
+}
+
+impl<K: Eq + Hash + Clone> BidirectionalBfs<HashMapStorage<K, usize>> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// One side of a [`BidirectionalBfs`]: the nodes at distance `depth` that were not expanded yet.
+struct Side<K> {
+    frontier: Vec<K>,
+    depth: usize,
+}
+
+impl<K> Side<K> {
+    fn new<S: Storage<K, usize>>(roots: impl IntoIterator<Item = K>, storage: &mut S) -> Self
+    where
+        K: Clone,
+    {
+        let mut frontier = Vec::new();
+        for root in roots {
+            if storage.cost(&root).is_none() {
+                storage.insert(root.clone(), None, 0);
+                frontier.push(root);
+            }
+        }
+        Self { frontier, depth: 0 }
+    }
+
+    /// Expands the whole frontier. Returns the shortest path found through a node the other side
+    /// already reached, as its length and that node.
+    fn expand<S, I>(
+        &mut self,
+        storage: &mut S,
+        other: &S,
+        mut successors: impl FnMut(&K) -> I,
+    ) -> Option<(usize, K)>
+    where
+        K: Clone,
+        S: Storage<K, usize>,
+        I: IntoIterator<Item = K>,
+    {
+        let mut best = None::<(usize, K)>;
+        let mut next = Vec::new();
+        for key in std::mem::take(&mut self.frontier) {
+            stats::node_expanded();
+            for successor in successors(&key) {
+                if storage.cost(&successor).is_some() {
+                    stats::memo_hit();
+                    continue;
+                }
+                if let Some(rest) = other.cost(&successor) {
+                    let through = self.depth + 1 + rest;
+                    if best.as_ref().is_none_or(|(best, _)| through < *best) {
+                        best = Some((through, successor.clone()));
+                    }
+                }
+                storage.insert(successor.clone(), Some(key.clone()), self.depth + 1);
+                next.push(successor);
+            }
+        }
+        self.frontier = next;
+        self.depth += 1;
+        stats::queue_size(self.frontier.len());
+        best
+    }
+}
+
+impl<S> BidirectionalBfs<S> {
+    /// The shortest path from any of `from` to any of `to`, and its length. `predecessors` must be
+    /// the reverse of `successors`: the nodes that have a given node as a successor.
+    pub fn find_path<K, FI, RI>(
+        mut self,
+        from: impl IntoIterator<Item = K>,
+        to: impl IntoIterator<Item = K>,
+        mut successors: impl FnMut(&K) -> FI,
+        mut predecessors: impl FnMut(&K) -> RI,
+    ) -> Option<(Vec<K>, usize)>
+    where
+        K: Eq + Clone,
+        S: ParentStorage<K, usize>,
+        FI: IntoIterator<Item = K>,
+        RI: IntoIterator<Item = K>,
+    {
+        let mut forward = Side::new(from, &mut self.forward);
+        let mut backward = Side::new(to, &mut self.backward);
+        let mut best = forward
+            .frontier
+            .iter()
+            .find(|key| self.backward.cost(key).is_some())
+            .map(|key| (0, key.clone()));
+        loop {
+            // Every path of up to `forward.depth + backward.depth` edges has a node that both
+            // sides reached, so it was already found.
+            if best
+                .as_ref()
+                .is_some_and(|(best, _)| *best <= forward.depth + backward.depth + 1)
+            {
+                break;
+            }
+            if forward.frontier.is_empty() || backward.frontier.is_empty() {
+                break;
+            }
+            let found = if forward.frontier.len() <= backward.frontier.len() {
+                forward.expand(&mut self.forward, &self.backward, &mut successors)
+            } else {
+                backward.expand(&mut self.backward, &self.forward, &mut predecessors)
+            };
+            if let Some(found) = found {
+                if best.as_ref().is_none_or(|(best, _)| found.0 < *best) {
+                    best = Some(found);
+                }
+            }
+        }
+        let (length, meeting) = best?;
+        let mut path = Vec::new();
+        let mut walk_back = Some(&meeting);
+        while let Some(at) = walk_back {
+            path.push(at.clone());
+            walk_back = self.forward.parent(at);
+        }
+        path.reverse();
+        let mut walk_back = self.backward.parent(&meeting);
+        while let Some(at) = walk_back {
+            path.push(at.clone());
+            walk_back = self.backward.parent(at);
+        }
+        Some((path, length))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The direct edges are expensive, so the cheapest paths are found only after nodes have
+    /// already been reached - which trips [`LinearBfs`].
+    const EDGES: &[(usize, usize, u32)] = &[
+        (0, 3, 10),
+        (0, 1, 1),
+        (1, 3, 7),
+        (1, 2, 1),
+        (2, 3, 1),
+        (3, 4, 2),
+    ];
+
+    fn linear_dijkstra() -> LinearDijkstra<u32> {
+        let mut dijkstra = LinearDijkstra::new(5);
+        dijkstra.add_root(0, 0);
+        while let Some(node) = dijkstra.consider_next() {
+            for &(from, to, cost) in EDGES {
+                if from == node {
+                    dijkstra.add_edge(from, to, cost);
+                }
+            }
+        }
+        dijkstra
+    }
 
-    pub fn all_known(&self) -> impl Iterator<Item = &K> {
-        self.cells.keys()
+    #[test]
+    fn cheapest_paths_with_uneven_costs() {
+        let dijkstra = linear_dijkstra();
+        assert_eq!(
+            (0..5)
+                .map(|node| dijkstra.cost(&node).copied())
+                .collect::<Vec<_>>(),
+            [Some(0), Some(1), Some(2), Some(3), Some(5)]
+        );
+        assert_eq!(dijkstra.path_to(&4), [0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn nodes_are_expanded_once_in_cost_order() {
+        let mut dijkstra = HashMapDijkstra::<usize, u32>::new();
+        dijkstra.add_root(0, 0);
+        let mut expanded = Vec::new();
+        while let Some(node) = dijkstra.consider_next() {
+            expanded.push((node, *dijkstra.cost(&node).unwrap()));
+            for &(from, to, cost) in EDGES {
+                if from == node {
+                    dijkstra.add_edge(from, to, cost);
+                }
+            }
+        }
+        assert_eq!(expanded, [(0, 0), (1, 1), (2, 2), (3, 3), (4, 5)]);
+        assert_eq!(dijkstra.path_to(&3), [0, 1, 2, 3]);
+        assert_eq!(dijkstra.all_known().count(), 5);
+        assert!(!dijkstra.add_edge(0, 3, 10));
+    }
+
+    #[test]
+    fn astar_expands_less_toward_the_goal() {
+        // A 20x20 open grid, from the top left corner to the bottom right one.
+        const SIDE: usize = 20;
+        let neighbors = |idx: usize| {
+            let [x, y] = [idx % SIDE, idx / SIDE];
+            [
+                (x + 1 < SIDE).then(|| idx + 1),
+                (0 < x).then(|| idx - 1),
+                (y + 1 < SIDE).then(|| idx + SIDE),
+                (0 < y).then(|| idx - SIDE),
+            ]
+            .into_iter()
+            .flatten()
+        };
+        let goal = SIDE * SIDE - 1;
+
+        let mut dijkstra = LinearDijkstra::<usize>::new(SIDE * SIDE);
+        dijkstra.add_root(0, 0);
+        while let Some(idx) = dijkstra.consider_next() {
+            if idx == goal {
+                break;
+            }
+            for neighbor in neighbors(idx) {
+                dijkstra.add_edge(idx, neighbor, 1);
+            }
+        }
+
+        let mut astar = LinearAStar::new(SIDE * SIDE, |&idx| {
+            (SIDE - 1 - idx % SIDE) + (SIDE - 1 - idx / SIDE)
+        });
+        astar.add_root(0, 0);
+        while let Some(idx) = astar.consider_next() {
+            if idx == goal {
+                break;
+            }
+            for neighbor in neighbors(idx) {
+                astar.add_edge(idx, neighbor, 1);
+            }
+        }
+        assert_eq!(astar.cost(&goal), dijkstra.cost(&goal));
+        assert_eq!(astar.path_to(&goal).len(), 2 * SIDE - 1);
+        assert_eq!(astar.expanded(), 2 * SIDE - 1);
+        assert_eq!(dijkstra.expanded(), SIDE * SIDE);
+    }
+
+    #[test]
+    fn astar_with_uneven_costs() {
+        // Never overestimates: every node but the last is at least 1 away from node 4.
+        let mut astar = HashMapAStar::new(|&node: &usize| u32::from(node < 4));
+        astar.add_root(0, 0);
+        while let Some(node) = astar.consider_next() {
+            if node == 4 {
+                break;
+            }
+            for &(from, to, cost) in EDGES {
+                if from == node {
+                    astar.add_edge(from, to, cost);
+                }
+            }
+        }
+        assert_eq!(astar.cost(&4), Some(&5));
+        assert_eq!(astar.path_to(&4), [0, 1, 2, 3, 4]);
+        assert_eq!(astar.expanded(), 5);
+    }
+
+    #[test]
+    fn storages_agree() {
+        let edges = [(0, 1), (1, 2), (2, 0), (3, 4), (2, 5)];
+        fn explore<S, F>(
+            mut search: Search<usize, (), S, F>,
+            edges: &[(usize, usize)],
+        ) -> Vec<usize>
+        where
+            S: Storage<usize, ()>,
+            F: Frontier<usize, ()>,
+        {
+            search.add_root(0, ());
+            let mut order = Vec::new();
+            while let Some(node) = search.consider_next() {
+                order.push(node);
+                for &(from, to) in edges {
+                    if from == node {
+                        search.add_edge(from, to, ());
+                    }
+                }
+            }
+            let mut known = search.all_known().collect::<Vec<_>>();
+            known.sort();
+            assert_eq!(known, [0, 1, 2, 5]);
+            order
+        }
+        let order = explore(BitSetBfs::new(70), &edges);
+        assert_eq!(order, [0, 1, 2, 5]);
+        assert_eq!(explore(LinearBfs::new(6), &edges), order);
+        assert_eq!(explore(HashMapBfs::new(), &edges), order);
+
+        let mut bits = BitSetStorage::new(130);
+        for key in [0, 63, 64, 129] {
+            bits.insert(key, None, ());
+        }
+        assert_eq!(bits.all_known().collect::<Vec<_>>(), [0, 63, 64, 129]);
+        assert_eq!(bits.cost(&65), None);
+    }
+
+    #[test]
+    fn walk_with_successors() {
+        let successors = |&node: &usize| {
+            EDGES
+                .iter()
+                .filter(move |(from, _, _)| *from == node)
+                .map(|&(_, to, cost)| (to, cost))
+        };
+        let visits = LinearDijkstra::new(5)
+            .walk([(0, 0)], successors)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            visits,
+            [
+                (0, 0, None),
+                (1, 1, Some(0)),
+                (2, 2, Some(1)),
+                (3, 3, Some(2)),
+                (4, 5, Some(3)),
+            ]
+        );
+
+        let mut walk = HashMapDijkstra::new().walk([(0, 0)], successors);
+        assert_eq!(walk.find_first(|&node| node == 3), Some(3));
+        assert_eq!(walk.search().path_to(&3), [0, 1, 2, 3]);
+        // The goal was not expanded yet.
+        assert_eq!(walk.search().cost(&4), None);
+        assert_eq!(walk.find_first(|&node| node == 0), None);
+
+        let reachable = BitSetBfs::new(5)
+            .walk([(1, ())], |&node| successors(&node).map(|(to, _)| (to, ())))
+            .reachable_set();
+        assert_eq!(reachable, HashSet::from([1, 2, 3, 4]));
+    }
+
+    #[test]
+    fn bidirectional_meets_in_the_middle() {
+        // A 30x30 grid with a wall down the middle that has one gap near the bottom.
+        const SIDE: usize = 30;
+        let open = |idx: usize| idx % SIDE != SIDE / 2 || idx / SIDE == SIDE - 2;
+        let neighbors = move |&idx: &usize| {
+            let [x, y] = [idx % SIDE, idx / SIDE];
+            [
+                (x + 1 < SIDE).then(|| idx + 1),
+                (0 < x).then(|| idx - 1),
+                (y + 1 < SIDE).then(|| idx + SIDE),
+                (0 < y).then(|| idx - SIDE),
+            ]
+            .into_iter()
+            .flatten()
+            .filter(move |&neighbor| open(neighbor))
+        };
+        let [start, goal] = [0, SIDE - 1];
+
+        let (bfs_cost, bfs_stats) = stats::collect(|| {
+            let mut walk = LinearBfs::new(SIDE * SIDE).walk([(start, 0)], |idx| {
+                neighbors(idx).map(|neighbor| (neighbor, 1))
+            });
+            walk.find_first(|&idx| idx == goal)
+                .map(|goal| *walk.search().cost(&goal).unwrap())
+        });
+        let (found, stats) = stats::collect(|| {
+            LinearBidirectionalBfs::new(SIDE * SIDE).find_path(
+                [start],
+                [goal],
+                neighbors,
+                neighbors,
+            )
+        });
+        let (path, cost) = found.unwrap();
+        assert_eq!(Some(cost), bfs_cost);
+        assert_eq!(path.len(), cost + 1);
+        assert_eq!((path[0], path[cost]), (start, goal));
+        assert!(path
+            .windows(2)
+            .all(|pair| neighbors(&pair[0]).any(|neighbor| neighbor == pair[1])));
+        assert!(stats.nodes_expanded < bfs_stats.nodes_expanded);
+
+        let walled = |&idx: &usize| neighbors(&idx).filter(|&neighbor| neighbor / SIDE != SIDE - 2);
+        assert_eq!(
+            HashMapBidirectionalBfs::new().find_path([start], [goal], walled, walled),
+            None
+        );
+        assert_eq!(
+            LinearBidirectionalBfs::new(SIDE * SIDE).find_path(
+                [start],
+                [goal, start],
+                neighbors,
+                neighbors
+            ),
+            Some((vec![start], 0))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "out of order")]
+    fn bfs_rejects_uneven_costs() {
+        let mut bfs = LinearBfs::<u32>::new(5);
+        bfs.add_root(0, 0);
+        while let Some(node) = bfs.consider_next() {
+            for &(from, to, cost) in EDGES {
+                if from == node {
+                    bfs.add_edge(from, to, cost);
+                }
+            }
+        }
     }
 }
@@ -0,0 +1,132 @@
+use std::time::{Duration, Instant};
+
+/// Limits for a long search. The time limit counts from when the search starts.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Budget {
+    pub time_limit: Option<Duration>,
+    pub max_nodes: Option<u64>,
+}
+
+impl Budget {
+    pub fn unlimited() -> Self {
+        Self::default()
+    }
+
+    pub fn is_unlimited(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn start(&self) -> Meter {
+        Meter {
+            deadline: self.time_limit.map(|limit| Instant::now() + limit),
+            max_nodes: self.max_nodes,
+            nodes: 0,
+            exhausted: false,
+        }
+    }
+}
+
+/// Checking the clock is slower than expanding a node, so it is only done this often.
+const NODES_PER_CLOCK_CHECK: u64 = 1024;
+
+/// How much of a [`Budget`] a running search has spent.
+#[derive(Debug)]
+pub struct Meter {
+    deadline: Option<Instant>,
+    max_nodes: Option<u64>,
+    nodes: u64,
+    exhausted: bool,
+}
+
+impl Meter {
+    /// Returns whether there is budget left to expand another node. Once it runs out, it stays
+    /// out.
+    pub fn spend_node(&mut self) -> bool {
+        if self.exhausted {
+            return false;
+        }
+        self.nodes += 1;
+        let out_of_nodes = self
+            .max_nodes
+            .is_some_and(|max_nodes| max_nodes < self.nodes);
+        let out_of_time = self.nodes.is_multiple_of(NODES_PER_CLOCK_CHECK)
+            && self
+                .deadline
+                .is_some_and(|deadline| deadline <= Instant::now());
+        self.exhausted = out_of_nodes || out_of_time;
+        !self.exhausted
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.exhausted
+    }
+
+    /// [`Outcome::Final`] if the search finished within the budget.
+    pub fn outcome<T>(&self, value: T) -> Outcome<T> {
+        if self.exhausted {
+            Outcome::BestSoFar(value)
+        } else {
+            Outcome::Final(value)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome<T> {
+    Final(T),
+    /// The budget ran out. The value is achievable, but maybe not the best.
+    BestSoFar(T),
+}
+
+impl<T> Outcome<T> {
+    pub fn is_final(&self) -> bool {
+        matches!(self, Outcome::Final(_))
+    }
+
+    pub fn value(self) -> T {
+        match self {
+            Outcome::Final(value) | Outcome::BestSoFar(value) => value,
+        }
+    }
+
+    pub fn map<S>(self, f: impl FnOnce(T) -> S) -> Outcome<S> {
+        match self {
+            Outcome::Final(value) => Outcome::Final(f(value)),
+            Outcome::BestSoFar(value) => Outcome::BestSoFar(f(value)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn node_limit() {
+        let mut meter = Budget {
+            max_nodes: Some(3),
+            ..Budget::default()
+        }
+        .start();
+        assert!((0..3).all(|_| meter.spend_node()));
+        assert_eq!(meter.outcome(1), Outcome::Final(1));
+        assert!(!meter.spend_node());
+        assert!(!meter.spend_node());
+        assert_eq!(meter.outcome(1), Outcome::BestSoFar(1));
+    }
+
+    #[test]
+    fn time_limit() {
+        let mut meter = Budget {
+            time_limit: Some(Duration::ZERO),
+            ..Budget::default()
+        }
+        .start();
+        assert!(meter.spend_node());
+        assert!(!(0..NODES_PER_CLOCK_CHECK).all(|_| meter.spend_node()));
+        assert!(meter.is_exhausted());
+
+        let mut meter = Budget::unlimited().start();
+        assert!((0..10 * NODES_PER_CLOCK_CHECK).all(|_| meter.spend_node()));
+    }
+}
@@ -0,0 +1,210 @@
+use std::fmt::Display;
+use std::io;
+use std::ops::AddAssign;
+use std::path::{Path, PathBuf};
+
+use hashbrown::HashMap;
+
+use crate::answer::Answer;
+use crate::error::ParseError;
+use crate::solution::Part;
+
+pub const DEFAULT_ANSWERS_DIR: &str = "answers";
+
+pub fn answers_path(dir: impl AsRef<Path>, day: u8) -> PathBuf {
+    dir.as_ref().join(format!("day{}.txt", day))
+}
+
+/// The accepted answers of a single input, stored as `part_N: answer` lines.
+///
+/// Newlines and backslashes inside answers are escaped as `\n` and `\\`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ExpectedAnswers(HashMap<Part, Answer>);
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+fn unescape(input: &str, text: &str) -> Result<String, ParseError> {
+    let mut result = String::new();
+    let mut chars = text.char_indices();
+    while let Some((_, c)) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some((_, 'n')) => result.push('\n'),
+            Some((_, '\\')) => result.push('\\'),
+            Some((i, c)) => {
+                return Err(ParseError::new(
+                    input,
+                    &text[i..i + c.len_utf8()],
+                    "n or \\ after \\",
+                ));
+            }
+            None => {
+                return Err(ParseError::new(
+                    input,
+                    &text[text.len()..],
+                    "n or \\ after \\",
+                ))
+            }
+        }
+    }
+    Ok(result)
+}
+
+impl ExpectedAnswers {
+    pub fn parse(input: &str) -> Result<Self, ParseError> {
+        let mut result = Self::default();
+        for line in input.lines() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let (part_name, answer) = line
+                .split_once(": ")
+                .ok_or_else(|| ParseError::new(input, line, "part_N: ANSWER"))?;
+            let part = Part::ALL
+                .into_iter()
+                .find(|part| part.name() == part_name)
+                .ok_or_else(|| ParseError::new(input, part_name, "part_1 or part_2"))?;
+            if result.0.contains_key(&part) {
+                return Err(ParseError::new(input, part_name, "each part only once"));
+            }
+            result.0.insert(part, unescape(input, answer)?.into());
+        }
+        Ok(result)
+    }
+
+    /// Returns an empty set of answers if the file does not exist.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => {
+                Self::parse(&text).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err),
+        }
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, self.to_string())
+    }
+
+    pub fn get(&self, part: Part) -> Option<&Answer> {
+        self.0.get(&part)
+    }
+
+    pub fn set(&mut self, part: Part, answer: Answer) {
+        self.0.insert(part, answer);
+    }
+
+    pub fn check(&self, part: Part, actual: &Answer) -> CheckOutcome {
+        match self.get(part) {
+            None => CheckOutcome::Missing,
+            Some(expected) if expected == actual => CheckOutcome::Pass,
+            Some(expected) => CheckOutcome::Fail {
+                expected: expected.clone(),
+            },
+        }
+    }
+}
+
+impl Display for ExpectedAnswers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for part in Part::ALL {
+            if let Some(answer) = self.get(part) {
+                writeln!(f, "{}: {}", part.name(), escape(&answer.to_string()))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CheckOutcome {
+    Pass,
+    Fail { expected: Answer },
+    Missing,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CheckSummary {
+    pub passed: usize,
+    pub failed: usize,
+    pub missing: usize,
+}
+
+impl CheckSummary {
+    pub fn add(&mut self, outcome: &CheckOutcome) {
+        match outcome {
+            CheckOutcome::Pass => self.passed += 1,
+            CheckOutcome::Fail { .. } => self.failed += 1,
+            CheckOutcome::Missing => self.missing += 1,
+        }
+    }
+}
+
+impl AddAssign for CheckSummary {
+    fn add_assign(&mut self, other: Self) {
+        self.passed += other.passed;
+        self.failed += other.failed;
+        self.missing += other.missing;
+    }
+}
+
+impl Display for CheckSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} passed, {} failed, {} missing",
+            self.passed, self.failed, self.missing
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut answers = ExpectedAnswers::default();
+        answers.set(Part::One, Answer::Number(13140));
+        answers.set(Part::Two, Answer::Text("\n##..\\\n.##.".to_owned()));
+        let text = answers.to_string();
+        assert_eq!(text, "part_1: 13140\npart_2: \\n##..\\\\\\n.##.\n");
+        assert_eq!(ExpectedAnswers::parse(&text), Ok(answers));
+    }
+
+    #[test]
+    fn check_outcomes() {
+        let answers = ExpectedAnswers::parse("part_1: CMZ\n").unwrap();
+        assert_eq!(answers.check(Part::One, &"CMZ".into()), CheckOutcome::Pass);
+        assert_eq!(
+            answers.check(Part::One, &"MCD".into()),
+            CheckOutcome::Fail {
+                expected: "CMZ".into()
+            }
+        );
+        assert_eq!(
+            answers.check(Part::Two, &"MCD".into()),
+            CheckOutcome::Missing
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        let err = ExpectedAnswers::parse("part_1: 5\npart_3: 7\n").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 1));
+        let err = ExpectedAnswers::parse("part_1: 5\npart_1: 7\n").unwrap_err();
+        assert_eq!(err.expected, "each part only once");
+        let err = ExpectedAnswers::parse("part_1: a\\tb\n").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 11));
+    }
+}
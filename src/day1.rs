@@ -1,33 +1,80 @@
-use itertools::Itertools;
+use crate::error::{ParseError, SolveError};
+use crate::parsing::{blocks, integer};
 
 type RowData = Vec<usize>;
 
-pub fn generator(input: &str) -> Vec<RowData> {
-    input
-        .lines()
-        .batching(|it| {
-            Some(
-                it.map_while(|line| line.parse().ok())
-                    .collect::<Vec<usize>>(),
-            )
-            .filter(|list| !list.is_empty())
+pub fn generator(input: &str) -> Result<Vec<RowData>, ParseError> {
+    blocks(input)
+        .map(|block| {
+            block
+                .lines()
+                .map(|line| integer(input, line, "a calories number"))
+                .collect()
         })
-        .collect::<Vec<Vec<usize>>>()
+        .collect()
+}
+
+fn no_elves() -> SolveError {
+    SolveError::Empty("elves".to_owned())
 }
 
-pub fn part_1(input: &[RowData]) -> usize {
+pub fn part_1(input: &[RowData]) -> Result<usize, SolveError> {
     input
         .iter()
         .map(|elf_items| elf_items.iter().sum())
         .max()
-        .unwrap()
+        .ok_or_else(no_elves)
 }
 
-pub fn part_2(input: &[RowData]) -> usize {
+pub fn part_2(input: &[RowData]) -> Result<usize, SolveError> {
+    if input.is_empty() {
+        return Err(no_elves());
+    }
     let mut elves_totals = input
         .iter()
         .map(|elf_items| elf_items.iter().sum())
         .collect::<Vec<usize>>();
     elves_totals.sort_by_key(|&num| std::cmp::Reverse(num));
-    elves_totals.iter().take(3).sum()
+    Ok(elves_totals.iter().take(3).sum())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+1000
+2000
+3000
+
+4000
+
+5000
+6000
+
+7000
+8000
+9000
+
+10000
+";
+
+    #[test]
+    fn part_1_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part_1(&input), Ok(24000));
+    }
+
+    #[test]
+    fn part_2_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part_2(&input), Ok(45000));
+    }
+
+    #[test]
+    fn empty_input() {
+        let input = generator("").unwrap();
+        let empty = Err(SolveError::Empty("elves".to_owned()));
+        assert_eq!((part_1(&input), part_2(&input)), (empty.clone(), empty));
+    }
 }
@@ -1,19 +1,24 @@
 use std::iter::from_fn;
 
+use crate::error::ParseError;
+use crate::parsing::integer;
+
 #[derive(Debug)]
 pub enum Instruction {
     Noop,
     AddX(isize),
 }
 
-pub fn generator(input: &str) -> Vec<Instruction> {
+pub fn generator(input: &str) -> Result<Vec<Instruction>, ParseError> {
     input
         .lines()
         .map(|line| {
             if line == "noop" {
-                Instruction::Noop
+                Ok(Instruction::Noop)
+            } else if let Some(addition) = line.strip_prefix("addx ") {
+                Ok(Instruction::AddX(integer(input, addition, "a number")?))
             } else {
-                Instruction::AddX(line.split(' ').last().unwrap().parse().unwrap())
+                Err(ParseError::new(input, line, "noop or addx"))
             }
         })
         .collect()
@@ -75,3 +80,178 @@ pub fn part_2(input: &[Instruction]) -> String {
     }
     output
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+addx 15
+addx -11
+addx 6
+addx -3
+addx 5
+addx -1
+addx -8
+addx 13
+addx 4
+noop
+addx -1
+addx 5
+addx -1
+addx 5
+addx -1
+addx 5
+addx -1
+addx 5
+addx -1
+addx -35
+addx 1
+addx 24
+addx -19
+addx 1
+addx 16
+addx -11
+noop
+noop
+addx 21
+addx -15
+noop
+noop
+addx -3
+addx 9
+addx 1
+addx -3
+addx 8
+addx 1
+addx 5
+noop
+noop
+noop
+noop
+noop
+addx -36
+noop
+addx 1
+addx 7
+noop
+noop
+noop
+addx 2
+addx 6
+noop
+noop
+noop
+noop
+noop
+addx 1
+noop
+noop
+addx 7
+addx 1
+noop
+addx -13
+addx 13
+addx 7
+noop
+addx 1
+addx -33
+noop
+noop
+noop
+addx 2
+noop
+noop
+noop
+addx 8
+noop
+addx -1
+addx 2
+addx 1
+noop
+addx 17
+addx -9
+addx 1
+addx 1
+addx -3
+addx 11
+noop
+noop
+addx 1
+noop
+addx 1
+noop
+noop
+addx -13
+addx -19
+addx 1
+addx 3
+addx 26
+addx -30
+addx 12
+addx -1
+addx 3
+addx 1
+noop
+noop
+noop
+addx -9
+addx 18
+addx 1
+addx 2
+noop
+noop
+addx 9
+noop
+noop
+noop
+addx -1
+addx 2
+addx -37
+addx 1
+addx 3
+noop
+addx 15
+addx -21
+addx 22
+addx -6
+addx 1
+noop
+addx 2
+addx 1
+noop
+addx -10
+noop
+noop
+addx 20
+addx 1
+addx 2
+addx 2
+addx -6
+addx -11
+noop
+noop
+noop
+";
+
+    #[test]
+    fn part_1_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part_1(&input), 13140);
+    }
+
+    #[test]
+    fn part_2_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(
+            part_2(&input),
+            "\n\
+             ##..##..##..##..##..##..##..##..##..##..\n\
+             ###...###...###...###...###...###...###.\n\
+             ####....####....####....####....####....\n\
+             #####.....#####.....#####.....#####.....\n\
+             ######......######......######......####\n\
+             #######.......#######.......#######....."
+        );
+    }
+}
@@ -2,7 +2,9 @@ use std::cmp::Reverse;
 use std::collections::VecDeque;
 
 use itertools::Itertools;
-use regex::Regex;
+
+use crate::error::ParseError;
+use crate::parsing::{blocks, field, integer, integers, prefixed, Pattern};
 
 #[derive(Debug)]
 pub struct MonkeyDescription {
@@ -20,68 +22,91 @@ enum Operation {
     Squared,
 }
 
-pub fn generator(input: &str) -> Vec<MonkeyDescription> {
-    let header_pattern = Regex::new(r#"^Monkey (\d+):$"#).unwrap();
-    let startin_items_pattern = Regex::new(r#"^  Starting items: (.*)$"#).unwrap();
-    let operation_pattern = Regex::new(r#"^  Operation: new = old (.) (.*)$"#).unwrap();
-    let test_pattern = Regex::new(r#"^  Test: divisible by (\d+)$"#).unwrap();
-    let throw_pattern = Regex::new(r#"^    If (true|false): throw to monkey (\d+)$"#).unwrap();
-    input
-        .lines()
-        .batching(|it| {
-            let mut get_captures = |pattern: &Regex| {
-                let line = it.next()?;
-                pattern.captures(line)
-            };
-            let monkey_idx = get_captures(&header_pattern)?[1].parse().unwrap();
-            let starting_items = get_captures(&startin_items_pattern)?[1]
-                .split(", ")
-                .map(|txt| txt.parse().unwrap())
-                .collect();
-            let operation = {
-                let captures = get_captures(&operation_pattern)?;
-                match (&captures[1], &captures[2]) {
-                    ("+", num) => Operation::Add(num.parse().unwrap()),
-                    ("*", "old") => Operation::Squared,
-                    ("*", num) => Operation::Multiply(num.parse().unwrap()),
-                    _ => panic!("Invalid: {:?}", captures),
-                }
-            };
-            let test_division = get_captures(&test_pattern)?[1].parse().unwrap();
-            let mut throw_to = [0, 0];
-            for (i, cond) in [(1, "true"), (0, "false")] {
-                let captures = get_captures(&throw_pattern)?;
-                assert_eq!(&captures[1], cond);
-                throw_to[i] = captures[2].parse().unwrap();
+pub fn generator(input: &str) -> Result<Vec<MonkeyDescription>, ParseError> {
+    static HEADER: Pattern = Pattern::new(r"^Monkey (\d+):$");
+    static OPERATION: Pattern = Pattern::new(r"^new = old (.) (.*)$");
+    let mut result = Vec::<MonkeyDescription>::new();
+    let mut throw_targets = Vec::new();
+    for block in blocks(input) {
+        let mut lines = block.lines();
+        let mut next_line = |expected: &str| {
+            lines
+                .next()
+                .ok_or_else(|| ParseError::new(input, &block[block.len()..], expected))
+        };
+        let monkey_idx = {
+            let captures = HEADER.captures(input, next_line("Monkey N:")?, "Monkey N:")?;
+            let expected = format!("monkey number {}", result.len());
+            if integer::<usize>(input, &captures[1], &expected)? != result.len() {
+                return Err(ParseError::new(input, &captures[1], expected));
             }
-
-            if let Some(line) = it.next() {
-                assert_eq!(line, "");
+            result.len()
+        };
+        let starting_items = integers(
+            input,
+            field(input, next_line("the starting items")?, "Starting items")?,
+            ", ",
+            "a worry level",
+        )?;
+        let operation = {
+            let text = field(input, next_line("the operation")?, "Operation")?;
+            let captures = OPERATION.captures(input, text, "new = old followed by + or *")?;
+            match (&captures[1], &captures[2]) {
+                ("+", num) => Operation::Add(integer(input, num, "a number")?),
+                ("*", "old") => Operation::Squared,
+                ("*", num) => Operation::Multiply(integer(input, num, "a number or old")?),
+                _ => return Err(ParseError::new(input, &captures[1], "+ or *")),
             }
-            Some(MonkeyDescription {
-                monkey_idx,
-                starting_items,
-                operation,
-                test_division,
-                throw_to,
-            })
-        })
-        .collect()
+        };
+        let test_division = {
+            let text = field(input, next_line("the test")?, "Test")?;
+            let divisor = prefixed(input, text, "divisible by ")?;
+            match integer(input, divisor, "a divisor")? {
+                0 => return Err(ParseError::new(input, divisor, "a positive divisor")),
+                divisor => divisor,
+            }
+        };
+        let mut throw_to = [0, 0];
+        for (i, cond) in [(1, "true"), (0, "false")] {
+            let line = next_line(&format!("the {} throw target", cond))?;
+            let text = field(input, line, &format!("If {}", cond))?;
+            let target = prefixed(input, text, "throw to monkey ")?;
+            throw_to[i] = integer(input, target, "a monkey number")?;
+            throw_targets.push((throw_to[i], target));
+        }
+        if let Some(line) = lines.next() {
+            return Err(ParseError::new(input, line, "an empty line"));
+        }
+        result.push(MonkeyDescription {
+            monkey_idx,
+            starting_items,
+            operation,
+            test_division,
+            throw_to,
+        });
+    }
+    for (target, text) in throw_targets {
+        if result.len() <= target {
+            return Err(ParseError::new(input, text, "an existing monkey"));
+        }
+    }
+    Ok(result)
 }
 
 impl Operation {
     fn apply(&self, worry_level: usize) -> usize {
         match self {
-            Operation::Add(num) => worry_level + num,
-            Operation::Multiply(num) => worry_level * num,
-            Operation::Squared => worry_level * worry_level,
+            Operation::Add(num) => worry_level.checked_add(*num),
+            Operation::Multiply(num) => worry_level.checked_mul(*num),
+            Operation::Squared => worry_level.checked_mul(worry_level),
         }
+        .expect("worry level overflow")
     }
 }
 
 impl MonkeyDescription {
     fn throw_to(&self, worry_level: usize) -> usize {
-        if worry_level % self.test_division == 0 {
+        if worry_level.is_multiple_of(self.test_division) {
             self.throw_to[1]
         } else {
             self.throw_to[0]
@@ -124,9 +149,13 @@ impl State {
             .items
             .pop_front()
         {
-            let new_worry_level = (monkey_description.operation.apply(worry_level)
-                / divide_worry_level_by)
-                % self.lcm;
+            let mut new_worry_level =
+                monkey_description.operation.apply(worry_level) / divide_worry_level_by;
+            // Reducing modulo the LCM keeps the divisibility tests intact, but not once the
+            // worry levels get divided.
+            if divide_worry_level_by == 1 {
+                new_worry_level %= self.lcm;
+            }
             let throw_to = monkey_description.throw_to(new_worry_level);
             self.monkeys[throw_to].items.push_back(new_worry_level);
             self.monkeys[monkey_description.monkey_idx].times_inspected += 1;
@@ -154,18 +183,151 @@ impl State {
     }
 }
 
+#[derive(Debug, Clone)]
+pub struct Params {
+    pub rounds_with_relief: usize,
+    pub rounds_without_relief: usize,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            rounds_with_relief: 20,
+            rounds_without_relief: 10000,
+        }
+    }
+}
+
 pub fn part_1(input: &[MonkeyDescription]) -> usize {
+    part_1_with(input, &Params::default())
+}
+
+pub fn part_1_with(input: &[MonkeyDescription], params: &Params) -> usize {
     let mut state: State = input.into();
-    for _ in 0..20 {
+    for _ in 0..params.rounds_with_relief {
         state.run_round(input, 3);
     }
     state.monkey_business()
 }
 
 pub fn part_2(input: &[MonkeyDescription]) -> usize {
+    part_2_with(input, &Params::default())
+}
+
+pub fn part_2_with(input: &[MonkeyDescription], params: &Params) -> usize {
     let mut state: State = input.into();
-    for _ in 0..10000 {
+    for _ in 0..params.rounds_without_relief {
         state.run_round(input, 1);
     }
     state.monkey_business()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+Monkey 0:
+  Starting items: 79, 98
+  Operation: new = old * 19
+  Test: divisible by 23
+    If true: throw to monkey 2
+    If false: throw to monkey 3
+
+Monkey 1:
+  Starting items: 54, 65, 75, 74
+  Operation: new = old + 6
+  Test: divisible by 19
+    If true: throw to monkey 2
+    If false: throw to monkey 0
+
+Monkey 2:
+  Starting items: 79, 60, 97
+  Operation: new = old * old
+  Test: divisible by 13
+    If true: throw to monkey 1
+    If false: throw to monkey 3
+
+Monkey 3:
+  Starting items: 74
+  Operation: new = old + 3
+  Test: divisible by 17
+    If true: throw to monkey 0
+    If false: throw to monkey 1
+";
+
+    #[test]
+    fn part_1_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part_1(&input), 10605);
+    }
+
+    #[test]
+    fn part_2_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part_2(&input), 2713310158);
+    }
+
+    /// Keeps the full worry levels, without reducing them.
+    fn reference_monkey_business(
+        input: &[MonkeyDescription],
+        rounds: usize,
+        divide_worry_level_by: usize,
+    ) -> usize {
+        use num::Zero;
+        use num_bigint::BigUint;
+
+        let mut items = input
+            .iter()
+            .map(|monkey| {
+                monkey
+                    .starting_items
+                    .iter()
+                    .map(|&item| BigUint::from(item))
+                    .collect::<VecDeque<_>>()
+            })
+            .collect_vec();
+        let mut times_inspected = vec![0; input.len()];
+        for _ in 0..rounds {
+            for (i, monkey) in input.iter().enumerate() {
+                while let Some(worry_level) = items[i].pop_front() {
+                    times_inspected[i] += 1;
+                    let worry_level = match monkey.operation {
+                        Operation::Add(num) => worry_level + num,
+                        Operation::Multiply(num) => worry_level * num,
+                        Operation::Squared => &worry_level * &worry_level,
+                    } / divide_worry_level_by;
+                    let is_divisible = (&worry_level % monkey.test_division).is_zero();
+                    items[monkey.throw_to[usize::from(is_divisible)]].push_back(worry_level);
+                }
+            }
+        }
+        times_inspected.sort();
+        times_inspected.iter().rev().take(2).product()
+    }
+
+    #[test]
+    fn matches_reference_on_random_inputs() {
+        // Without relief the full worry levels get huge quickly, so only a few rounds.
+        let params = Params {
+            rounds_with_relief: 20,
+            rounds_without_relief: 8,
+        };
+        for seed in 0..20 {
+            let text = crate::random_input::generate(11, seed, 2 + seed as usize % 6).unwrap();
+            let input = generator(&text).unwrap();
+            assert_eq!(
+                part_1_with(&input, &params),
+                reference_monkey_business(&input, params.rounds_with_relief, 3),
+                "part 1 of\n{}",
+                text
+            );
+            assert_eq!(
+                part_2_with(&input, &params),
+                reference_monkey_business(&input, params.rounds_without_relief, 1),
+                "part 2 of\n{}",
+                text
+            );
+        }
+    }
+}
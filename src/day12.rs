@@ -1,6 +1,10 @@
 use std::fmt::{Display, Write};
 
-use crate::bfs::LinearBfs;
+use crate::bfs::{LinearAStar, LinearBfs};
+use crate::error::{ParseError, SolveError};
+use crate::parsing::grid;
+use crate::solution::Part;
+use crate::visualize::{Frame, Rgb, Visualize};
 use crate::vmatrix::VMatrix;
 
 #[derive(Debug)]
@@ -26,11 +30,10 @@ impl Display for HeightMap {
     }
 }
 
-pub fn generator(input: &str) -> HeightMap {
-    let heights = input
-        .chars()
-        .map(|c| if c == '\n' { None } else { Some(c) })
-        .collect::<VMatrix<char>>();
+pub fn generator(input: &str) -> Result<HeightMap, ParseError> {
+    let heights = grid(input, input, "squares", "a height, S or E", |c| {
+        matches!(c, 'a'..='z' | 'S' | 'E').then_some(c)
+    })?;
     let mut start = None;
     let mut end = None;
     let heights = heights.map(|i, c| {
@@ -47,45 +50,141 @@ pub fn generator(input: &str) -> HeightMap {
         };
         c as usize - 'a' as usize
     });
-    HeightMap {
-        start: start.unwrap(),
-        end: end.unwrap(),
+    Ok(HeightMap {
+        start: start.ok_or_else(|| ParseError::at_end(input, "a start square marked S"))?,
+        end: end.ok_or_else(|| ParseError::at_end(input, "an end square marked E"))?,
         heights,
-    }
+    })
 }
 
-pub fn part_1(input: &HeightMap) -> usize {
-    let mut bfs = LinearBfs::new(input.heights.values.len());
-    bfs.add_root(input.start, 0);
-    while let Some(idx) = bfs.consider_next() {
-        if idx == input.end {
-            return *bfs.cost(idx).unwrap();
-        }
-        let this_height = input.heights.values[idx];
-        for neighbor in input.heights.neighbors_no_diag(idx) {
-            let neighbor_height = input.heights.values[neighbor];
-            if neighbor_height <= this_height + 1 {
-                bfs.add_edge(idx, neighbor, 1);
+/// The search at the moment it considers a square.
+struct SearchView<'a> {
+    map: &'a HeightMap,
+    reached: &'a dyn Fn(usize) -> bool,
+    path: &'a dyn Fn() -> Vec<usize>,
+}
+
+impl Visualize for SearchView<'_> {
+    fn frame(&self) -> Frame {
+        let heights = &self.map.heights;
+        let mut frame = Frame::new(heights.cols, heights.rows, Rgb::BLACK);
+        let cell = |idx: usize| [idx % heights.cols, idx / heights.cols];
+        for (idx, height) in heights.values.iter().enumerate() {
+            let mut color = Rgb::DARK_GRAY.mix(Rgb::WHITE, *height as f64 / 25.0);
+            if (self.reached)(idx) {
+                color = color.mix(Rgb::BLUE, 0.5);
             }
+            frame.set(cell(idx), color);
         }
+        for idx in (self.path)() {
+            frame.set(cell(idx), Rgb::YELLOW);
+        }
+        frame.set(cell(self.map.start), Rgb::GREEN);
+        frame.set(cell(self.map.end), Rgb::RED);
+        frame
     }
-    0
 }
 
-pub fn part_2(input: &HeightMap) -> usize {
-    let mut bfs = LinearBfs::new(input.heights.values.len());
-    bfs.add_root(input.end, 0);
-    while let Some(idx) = bfs.consider_next() {
+fn climb(input: &HeightMap, observe: &mut dyn FnMut(&dyn Visualize)) -> Result<usize, SolveError> {
+    let cols = input.heights.cols;
+    let [end_x, end_y] = [input.end % cols, input.end / cols];
+    let astar = LinearAStar::new(input.heights.values.len(), |&idx| {
+        (idx % cols).abs_diff(end_x) + (idx / cols).abs_diff(end_y)
+    });
+    let mut walk = astar.walk([(input.start, 0)], |&idx| {
         let this_height = input.heights.values[idx];
-        if this_height == 0 {
-            return *bfs.cost(idx).unwrap();
+        input
+            .heights
+            .neighbors_no_diag(idx)
+            .filter(move |&neighbor| input.heights.values[neighbor] <= this_height + 1)
+            .map(|neighbor| (neighbor, 1))
+    });
+    while let Some((idx, cost, _)) = walk.next() {
+        let search = walk.search();
+        observe(&SearchView {
+            map: input,
+            reached: &|idx| search.cost(&idx).is_some(),
+            path: &|| search.path_to(&idx),
+        });
+        if idx == input.end {
+            return Ok(cost);
         }
-        for neighbor in input.heights.neighbors_no_diag(idx) {
-            let neighbor_height = input.heights.values[neighbor];
-            if this_height <= neighbor_height + 1 {
-                bfs.add_edge(idx, neighbor, 1);
-            }
+    }
+    Err(SolveError::Unreachable)
+}
+
+fn descend(
+    input: &HeightMap,
+    observe: &mut dyn FnMut(&dyn Visualize),
+) -> Result<usize, SolveError> {
+    let bfs = LinearBfs::new(input.heights.values.len());
+    let mut walk = bfs.walk([(input.end, 0)], |&idx| {
+        let this_height = input.heights.values[idx];
+        input
+            .heights
+            .neighbors_no_diag(idx)
+            .filter(move |&neighbor| this_height <= input.heights.values[neighbor] + 1)
+            .map(|neighbor| (neighbor, 1))
+    });
+    while let Some((idx, cost, _)) = walk.next() {
+        let search = walk.search();
+        observe(&SearchView {
+            map: input,
+            reached: &|idx| search.cost(&idx).is_some(),
+            path: &|| search.path_to(&idx),
+        });
+        if input.heights.values[idx] == 0 {
+            return Ok(cost);
         }
     }
-    0
+    Err(SolveError::Unreachable)
+}
+
+pub fn part_1(input: &HeightMap) -> Result<usize, SolveError> {
+    climb(input, &mut |_| {})
+}
+
+pub fn part_2(input: &HeightMap) -> Result<usize, SolveError> {
+    descend(input, &mut |_| {})
+}
+
+/// Shows the search spreading, with the path to the square it considers.
+pub fn visualize(input: &HeightMap, part: Part, observe: &mut dyn FnMut(&dyn Visualize)) {
+    // Without a path, the frames show the search running out of squares.
+    let _ = match part {
+        Part::One => climb(input, observe),
+        Part::Two => descend(input, observe),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+Sabqponm
+abcryxxl
+accszExk
+acctuvwj
+abdefghi
+";
+
+    #[test]
+    fn part_1_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part_1(&input), Ok(31));
+    }
+
+    #[test]
+    fn part_2_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part_2(&input), Ok(29));
+    }
+
+    #[test]
+    fn walled_off() {
+        let input = generator("Sz\nzE\n").unwrap();
+        assert_eq!(part_1(&input), Err(SolveError::Unreachable));
+        assert_eq!(part_2(&input), Err(SolveError::Unreachable));
+    }
 }
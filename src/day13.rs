@@ -1,7 +1,11 @@
 use std::cmp::Ordering;
+use std::str::FromStr;
 
 use itertools::Itertools;
 
+use crate::error::{parse_part, ParseError};
+use crate::parsing::blocks;
+
 #[derive(Debug)]
 pub enum Token {
     Number(usize),
@@ -15,35 +19,54 @@ pub enum Packet {
     List(Vec<Packet>),
 }
 
-impl From<&str> for Packet {
-    fn from(line: &str) -> Self {
+impl Packet {
+    fn parse(input: &str, line: &str) -> Result<Self, ParseError> {
         let mut tokens = Vec::new();
-        let mut number = String::new();
-        for c in line.chars() {
+        let mut number_start = None;
+        let flush_number = |tokens: &mut Vec<Token>, number_start: &mut Option<usize>, end| {
+            if let Some(start) = number_start.take() {
+                tokens.push(Token::Number(parse_part(
+                    input,
+                    &line[start..end],
+                    "a number",
+                )?));
+            }
+            Ok(())
+        };
+        for (i, c) in line.char_indices() {
             match c {
                 '[' => {
-                    assert!(number.is_empty());
+                    if number_start.is_some() {
+                        return Err(ParseError::new(input, &line[i..i + 1], "',' or ']'"));
+                    }
                     tokens.push(Token::Open);
                 }
                 ']' => {
-                    if !number.is_empty() {
-                        tokens.push(Token::Number(number.parse().unwrap()));
-                        number.clear();
-                    }
+                    flush_number(&mut tokens, &mut number_start, i)?;
                     tokens.push(Token::Close);
                 }
                 ',' => {
-                    if !number.is_empty() {
-                        tokens.push(Token::Number(number.parse().unwrap()));
-                        number.clear();
-                    }
+                    flush_number(&mut tokens, &mut number_start, i)?;
+                }
+                '0'..='9' => {
+                    number_start.get_or_insert(i);
                 }
                 _ => {
-                    number.push(c);
+                    return Err(ParseError::new(
+                        input,
+                        &line[i..i + c.len_utf8()],
+                        "a digit, ',', '[' or ']'",
+                    ));
                 }
             }
         }
-        assert!(number.is_empty());
+        if let Some(start) = number_start {
+            return Err(ParseError::new(
+                input,
+                &line[start..],
+                "a number inside a list",
+            ));
+        }
         let mut parse_stack = Vec::new();
         parse_stack.push(Vec::<Packet>::new());
         for token in tokens.into_iter() {
@@ -55,6 +78,9 @@ impl From<&str> for Packet {
                     parse_stack.push(Vec::<Packet>::new());
                 }
                 Token::Close => {
+                    if parse_stack.len() < 2 {
+                        return Err(ParseError::new(input, line, "balanced brackets"));
+                    }
                     let packet = Packet::List(parse_stack.pop().unwrap());
                     parse_stack.last_mut().unwrap().push(packet);
                 }
@@ -63,23 +89,30 @@ impl From<&str> for Packet {
         parse_stack
             .into_iter()
             .exactly_one()
-            .unwrap()
-            .into_iter()
-            .exactly_one()
-            .unwrap()
+            .ok()
+            .and_then(|packets| packets.into_iter().exactly_one().ok())
+            .ok_or_else(|| ParseError::new(input, line, "a single balanced packet"))
     }
 }
 
-pub fn generator(input: &str) -> Vec<[Packet; 2]> {
-    input
-        .lines()
-        .batching(|it| {
-            let lines = it.take_while(|line| !line.is_empty()).collect_vec();
-            match lines.len() {
-                0 => None,
-                2 => Some([lines[0].into(), lines[1].into()]),
-                _ => panic!(),
-            }
+impl FromStr for Packet {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s, s)
+    }
+}
+
+pub fn generator(input: &str) -> Result<Vec<[Packet; 2]>, ParseError> {
+    blocks(input)
+        .map(|block| match block.lines().collect_vec().as_slice() {
+            [first, second] => Ok([Packet::parse(input, first)?, Packet::parse(input, second)?]),
+            [_, _, extra, ..] => Err(ParseError::new(input, extra, "an empty line")),
+            _ => Err(ParseError::new(
+                input,
+                &block[block.len()..],
+                "a second packet",
+            )),
         })
         .collect()
 }
@@ -140,7 +173,7 @@ pub fn part_2(input: &[[Packet; 2]]) -> usize {
         .flatten()
         .map(|packet| (packet, false))
         .collect_vec();
-    let divider_packets = ["[[2]]".into(), "[[6]]".into()];
+    let divider_packets = ["[[2]]".parse().unwrap(), "[[6]]".parse().unwrap()];
     packets.extend(divider_packets.iter().map(|packet| (packet, true)));
     packets.sort();
     packets
@@ -157,3 +190,46 @@ pub fn part_2(input: &[[Packet; 2]]) -> usize {
         )
         .product()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+[1,1,3,1,1]
+[1,1,5,1,1]
+
+[[1],[2,3,4]]
+[[1],4]
+
+[9]
+[[8,7,6]]
+
+[[4,4],4,4]
+[[4,4],4,4,4]
+
+[7,7,7,7]
+[7,7,7]
+
+[]
+[3]
+
+[[[]]]
+[[]]
+
+[1,[2,[3,[4,[5,6,7]]]],8,9]
+[1,[2,[3,[4,[5,6,0]]]],8,9]
+";
+
+    #[test]
+    fn part_1_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part_1(&input), 13);
+    }
+
+    #[test]
+    fn part_2_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part_2(&input), 140);
+    }
+}
@@ -4,23 +4,38 @@ use std::ops::Index;
 use hashbrown::HashMap;
 use itertools::Itertools;
 
+use crate::error::{ParseError, SolveError};
+use crate::parsing::{integer, list, pair};
+use crate::solution::Part;
+use crate::visualize::{Frame, Rgb, Visualize};
+
 type Coord = [isize; 2];
 type RockPath = Vec<Coord>;
 
-pub fn generator(input: &str) -> Vec<RockPath> {
+const SOURCE: Coord = [500, 0];
+
+pub fn generator(input: &str) -> Result<Vec<RockPath>, ParseError> {
     input
         .lines()
         .map(|line| {
-            line.split(" -> ")
-                .map(|coord| {
-                    coord
-                        .split(',')
-                        .map(|num| num.parse().unwrap())
-                        .collect_vec()
-                        .try_into()
-                        .unwrap()
-                })
-                .collect()
+            let mut prev = None::<Coord>;
+            list(line, " -> ", |coord_text| {
+                let (x, y) = pair(input, coord_text, ",", "a coordinate like 498,4")?;
+                let coord = [
+                    integer(input, x, "a number")?,
+                    integer(input, y, "a number")?,
+                ];
+                if let Some(prev) = prev.replace(coord) {
+                    if prev[0] != coord[0] && prev[1] != coord[1] {
+                        return Err(ParseError::new(
+                            input,
+                            coord_text,
+                            "a coordinate in a straight line from the previous one",
+                        ));
+                    }
+                }
+                Ok(coord)
+            })
         })
         .collect()
 }
@@ -97,12 +112,21 @@ impl CaveMap {
         }
     }
 
+    fn view(&self, min: Coord, max: Coord, floor: Option<isize>) -> CaveView<'_> {
+        CaveView {
+            cave_map: self,
+            min,
+            max,
+            floor,
+        }
+    }
+
     fn add_sand(&mut self, pos: Coord) {
         self.objects.insert(pos, Object::Sand);
     }
 
-    fn lowest(&self) -> isize {
-        self.objects.keys().map(|[_, y]| *y).max().unwrap()
+    fn lowest(&self) -> Option<isize> {
+        self.objects.keys().map(|[_, y]| *y).max()
     }
 
     fn trace_sand(
@@ -128,42 +152,169 @@ impl CaveMap {
     }
 }
 
-pub fn part_1(input: &[RockPath]) -> usize {
+/// The cave within fixed bounds, so that all the frames have the same size.
+struct CaveView<'a> {
+    cave_map: &'a CaveMap,
+    min: Coord,
+    max: Coord,
+    floor: Option<isize>,
+}
+
+impl Visualize for CaveView<'_> {
+    fn frame(&self) -> Frame {
+        let [width, height] = [0, 1].map(|i| (self.max[i] - self.min[i] + 1) as usize);
+        let mut frame = Frame::new(width, height, Rgb::BLACK);
+        for (coord, object) in self.cave_map.objects.iter() {
+            let color = match object {
+                Object::Air => continue,
+                Object::Rock => Rgb::GRAY,
+                Object::Sand => Rgb::SAND,
+            };
+            let [x, y] = coord_op(*coord, self.min, |c, m| c - m);
+            if 0 <= x && 0 <= y {
+                frame.set([x as usize, y as usize], color);
+            }
+        }
+        if let Some(floor) = self.floor {
+            for x in 0..width {
+                frame.set([x, (floor - self.min[1]) as usize], Rgb::GRAY);
+            }
+        }
+        frame.set([(500 - self.min[0]) as usize, 0], Rgb::YELLOW);
+        frame
+    }
+}
+
+fn fill_to_abyss(
+    input: &[RockPath],
+    observe: &mut dyn FnMut(&dyn Visualize),
+) -> Result<usize, SolveError> {
     let mut cave_map = CaveMap::default();
     for rock_path in input.iter() {
         cave_map.add_rock_path(rock_path);
     }
-    let max = cave_map.lowest();
-    for i in 0.. {
-        let final_pos = cave_map.trace_sand([500, 0], |[_, y]| y < max);
-        if let Ok(pos) = final_pos {
-            cave_map.add_sand(pos);
-        } else {
-            return i;
+    let max = cave_map.lowest().ok_or(SolveError::NoRocks)?;
+    let (min_x, max_x) = cave_map
+        .objects
+        .keys()
+        .map(|[x, _]| *x)
+        .chain([500])
+        .minmax()
+        .into_option()
+        .unwrap();
+    let (view_min, view_max) = ([min_x - 1, 0], [max_x + 1, max]);
+    observe(&cave_map.view(view_min, view_max, None));
+    let mut num_rested = 0;
+    while let Ok(pos) = cave_map.trace_sand(SOURCE, |[_, y]| y < max) {
+        cave_map.add_sand(pos);
+        observe(&cave_map.view(view_min, view_max, None));
+        num_rested += 1;
+        if pos == SOURCE {
+            break;
         }
     }
-    0
+    Ok(num_rested)
 }
 
-pub fn part_2(input: &[RockPath]) -> usize {
+fn fill_to_source(
+    input: &[RockPath],
+    observe: &mut dyn FnMut(&dyn Visualize),
+) -> Result<usize, SolveError> {
     let mut cave_map = CaveMap::default();
     for rock_path in input.iter() {
         cave_map.add_rock_path(rock_path);
     }
-    let lowest = cave_map.lowest() + 1; // floor is +2 - so one above it is +1
-    for i in 1.. {
-        let final_pos = cave_map.trace_sand([500, 0], |[_, y]| y < lowest);
-        match final_pos {
-            Ok(pos) => {
-                cave_map.add_sand(pos);
-                if pos == [500, 0] {
-                    return i;
-                }
-            }
-            Err(pos) => {
-                cave_map.add_sand(pos);
-            }
+    let lowest = cave_map.lowest().ok_or(SolveError::NoRocks)? + 1; // floor is +2 - so one above it is +1
+    let (view_min, view_max) = ([500 - lowest - 1, 0], [500 + lowest + 1, lowest + 1]);
+    observe(&cave_map.view(view_min, view_max, Some(lowest + 1)));
+    let mut num_rested = 0;
+    loop {
+        // Sand that reaches the floor rests there too.
+        let pos = cave_map
+            .trace_sand(SOURCE, |[_, y]| y < lowest)
+            .unwrap_or_else(|pos| pos);
+        cave_map.add_sand(pos);
+        observe(&cave_map.view(view_min, view_max, Some(lowest + 1)));
+        num_rested += 1;
+        if pos == SOURCE {
+            return Ok(num_rested);
+        }
+    }
+}
+
+pub fn part_1(input: &[RockPath]) -> Result<usize, SolveError> {
+    fill_to_abyss(input, &mut |_| {})
+}
+
+pub fn part_2(input: &[RockPath]) -> Result<usize, SolveError> {
+    fill_to_source(input, &mut |_| {})
+}
+
+/// Shows the cave after each unit of sand comes to rest.
+pub fn visualize(input: &[RockPath], part: Part, observe: &mut dyn FnMut(&dyn Visualize)) {
+    // An empty cave has nothing to show.
+    let _ = match part {
+        Part::One => fill_to_abyss(input, observe),
+        Part::Two => fill_to_source(input, observe),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+498,4 -> 498,6 -> 496,6
+503,4 -> 502,4 -> 502,9 -> 494,9
+";
+
+    #[test]
+    fn part_1_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part_1(&input), Ok(24));
+    }
+
+    #[test]
+    fn part_2_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part_2(&input), Ok(93));
+    }
+
+    #[test]
+    fn no_rocks() {
+        let input = generator("").unwrap();
+        assert_eq!(part_1(&input), Err(SolveError::NoRocks));
+        assert_eq!(part_2(&input), Err(SolveError::NoRocks));
+    }
+
+    #[test]
+    fn plugged_source() {
+        let input = generator("499,1 -> 501,1").unwrap();
+        assert_eq!(part_1(&input), Ok(1));
+        assert_eq!(part_2(&input), Ok(1));
+    }
+
+    #[test]
+    fn cave_map_display() {
+        let mut cave_map = CaveMap::default();
+        for rock_path in generator(EXAMPLE).unwrap().iter() {
+            cave_map.add_rock_path(rock_path);
+        }
+        let max = cave_map.lowest().unwrap();
+        while let Ok(pos) = cave_map.trace_sand(SOURCE, |[_, y]| y < max) {
+            cave_map.add_sand(pos);
         }
+        assert_eq!(
+            cave_map.to_string(),
+            "\n\
+             ......o...\n\
+             .....ooo..\n\
+             ....#ooo##\n\
+             ...o#ooo#.\n\
+             ..###ooo#.\n\
+             ....oooo#.\n\
+             .o.ooooo#.\n\
+             #########."
+        );
     }
-    0
 }
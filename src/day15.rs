@@ -4,6 +4,9 @@ use std::ops::RangeInclusive;
 use hashbrown::HashSet;
 use itertools::Itertools;
 
+use crate::error::{ParseError, SolveError};
+use crate::parsing::{integer, Pattern};
+
 type Coord = [isize; 2];
 
 #[derive(Debug)]
@@ -12,18 +15,22 @@ pub struct SensorInput {
     beacon: Coord,
 }
 
-pub fn generator(input: &str) -> Vec<SensorInput> {
-    let pattern =
-        regex::Regex::new(r#"^Sensor at x=(.+?), y=(.+?): closest beacon is at x=(.+?), y=(.+?)$"#)
-            .unwrap();
+pub fn generator(input: &str) -> Result<Vec<SensorInput>, ParseError> {
+    static SENSOR: Pattern =
+        Pattern::new(r"^Sensor at x=(.+?), y=(.+?): closest beacon is at x=(.+?), y=(.+?)$");
     input
         .lines()
         .map(|line| {
-            let captures = pattern.captures(line).unwrap();
-            SensorInput {
-                sensor: [captures[1].parse().unwrap(), captures[2].parse().unwrap()],
-                beacon: [captures[3].parse().unwrap(), captures[4].parse().unwrap()],
-            }
+            let captures = SENSOR.captures(
+                input,
+                line,
+                "Sensor at x=N, y=N: closest beacon is at x=N, y=N",
+            )?;
+            let coord = |i: usize| integer(input, &captures[i], "a number");
+            Ok(SensorInput {
+                sensor: [coord(1)?, coord(2)?],
+                beacon: [coord(3)?, coord(4)?],
+            })
         })
         .collect()
 }
@@ -75,8 +82,27 @@ fn normalize_ranges_for_row(
     )
 }
 
+#[derive(Debug, Clone)]
+pub struct Params {
+    pub row: isize,
+    pub search_bound: isize,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            row: 2000000,
+            search_bound: 4000000,
+        }
+    }
+}
+
 pub fn part_1(input: &[SensorInput]) -> usize {
-    let row = 2000000;
+    part_1_with(input, &Params::default())
+}
+
+pub fn part_1_with(input: &[SensorInput], params: &Params) -> usize {
+    let row = params.row;
     let num_covered_at_row: usize = normalize_ranges_for_row(input, row)
         .map(|range| (range.end() - range.start() + 1) as usize)
         .sum();
@@ -113,8 +139,9 @@ fn find_opening(
                 }
             }
             (Some(prev_range), Some(next_range)) => {
-                if *prev_range.end() + 2 == *next_range.start() {
-                    return Some(prev_range.end() + 1);
+                let col = prev_range.end() + 1;
+                if col + 1 == *next_range.start() && allowed_in.contains(&col) {
+                    return Some(col);
                 }
             }
         }
@@ -122,13 +149,133 @@ fn find_opening(
     None
 }
 
-pub fn part_2(input: &[SensorInput]) -> usize {
+pub fn part_2(input: &[SensorInput]) -> Result<usize, SolveError> {
+    part_2_with(input, &Params::default())
+}
+
+pub fn part_2_with(input: &[SensorInput], params: &Params) -> Result<usize, SolveError> {
     const X_MULTIIPLIER: isize = 4000000;
-    let allowed_in = 0..=X_MULTIIPLIER;
+    let allowed_in = 0..=params.search_bound;
     for row in allowed_in.clone() {
         if let Some(col) = find_opening(allowed_in.clone(), normalize_ranges_for_row(input, row)) {
-            return (col * X_MULTIIPLIER + row) as usize;
+            return Ok((col * X_MULTIIPLIER + row) as usize);
+        }
+    }
+    Err(SolveError::NoGapFound)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+Sensor at x=2, y=18: closest beacon is at x=-2, y=15
+Sensor at x=9, y=16: closest beacon is at x=10, y=16
+Sensor at x=13, y=2: closest beacon is at x=15, y=3
+Sensor at x=12, y=14: closest beacon is at x=10, y=16
+Sensor at x=10, y=20: closest beacon is at x=10, y=16
+Sensor at x=14, y=17: closest beacon is at x=10, y=16
+Sensor at x=8, y=7: closest beacon is at x=2, y=10
+Sensor at x=2, y=0: closest beacon is at x=2, y=10
+Sensor at x=0, y=11: closest beacon is at x=2, y=10
+Sensor at x=20, y=14: closest beacon is at x=25, y=17
+Sensor at x=17, y=20: closest beacon is at x=21, y=22
+Sensor at x=16, y=7: closest beacon is at x=15, y=3
+Sensor at x=14, y=3: closest beacon is at x=15, y=3
+Sensor at x=20, y=1: closest beacon is at x=15, y=3
+";
+
+    const EXAMPLE_PARAMS: Params = Params {
+        row: 10,
+        search_bound: 20,
+    };
+
+    #[test]
+    fn part_1_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part_1_with(&input, &EXAMPLE_PARAMS), 26);
+    }
+
+    #[test]
+    fn part_2_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part_2_with(&input, &EXAMPLE_PARAMS), Ok(56000011));
+    }
+
+    #[test]
+    fn fully_covered() {
+        let input = generator("Sensor at x=10, y=10: closest beacon is at x=10, y=40").unwrap();
+        assert_eq!(
+            part_2_with(&input, &EXAMPLE_PARAMS),
+            Err(SolveError::NoGapFound)
+        );
+    }
+
+    fn is_covered(input: &[SensorInput], cell: Coord) -> bool {
+        input.iter().any(|si| {
+            si.sensor[0].abs_diff(cell[0]) + si.sensor[1].abs_diff(cell[1])
+                <= si.detection_distance()
+        })
+    }
+
+    /// Checks every cell of the row that any sensor could reach.
+    fn reference_part_1(input: &[SensorInput], row: isize) -> usize {
+        let min_x = input
+            .iter()
+            .map(|si| si.sensor[0] - si.detection_distance() as isize)
+            .min()
+            .unwrap();
+        let max_x = input
+            .iter()
+            .map(|si| si.sensor[0] + si.detection_distance() as isize)
+            .max()
+            .unwrap();
+        (min_x..=max_x)
+            .filter(|&x| is_covered(input, [x, row]))
+            .filter(|&x| !input.iter().any(|si| si.beacon == [x, row]))
+            .count()
+    }
+
+    /// Checks every cell of the search area.
+    fn reference_part_2(input: &[SensorInput], search_bound: isize) -> usize {
+        let [x, y] = (0..=search_bound)
+            .cartesian_product(0..=search_bound)
+            .map(|(x, y)| [x, y])
+            .find(|&cell| !is_covered(input, cell))
+            .unwrap();
+        (x * 4000000 + y) as usize
+    }
+
+    #[test]
+    fn matches_reference_on_random_inputs() {
+        use crate::random_input::{day15_with, Rng};
+
+        for seed in 0..20 {
+            let text = day15_with(&mut Rng::new(seed), 20, seed as usize);
+            let input = generator(&text).unwrap();
+            for row in [-30, -5, 0, 7, 13, 20, 42] {
+                let params = Params {
+                    row,
+                    search_bound: 20,
+                };
+                assert_eq!(
+                    part_1_with(&input, &params),
+                    reference_part_1(&input, row),
+                    "row {} of\n{}",
+                    row,
+                    text
+                );
+            }
+            let params = Params {
+                row: 0,
+                search_bound: 20,
+            };
+            assert_eq!(
+                part_2_with(&input, &params),
+                Ok(reference_part_2(&input, params.search_bound)),
+                "{}",
+                text
+            );
         }
     }
-    0
 }
@@ -1,9 +1,13 @@
 use std::iter::repeat_with;
 
 use hashbrown::HashMap;
-use itertools::Itertools;
+use itertools::{Either, Itertools};
 
 use crate::bfs::LinearBfs;
+use crate::budget::{Budget, Meter, Outcome};
+use crate::error::{ParseError, SolveError};
+use crate::parsing::{integer, list, Pattern};
+use crate::stats;
 
 #[derive(Debug)]
 pub struct ValveDescription {
@@ -12,21 +16,36 @@ pub struct ValveDescription {
     tunnels: Vec<String>,
 }
 
-pub fn generator(input: &str) -> Vec<ValveDescription> {
-    let pattern =
-        regex::Regex::new(r#"Valve (\w+) has flow rate=(\d+); tunnels? leads? to valves? (.*)$"#)
-            .unwrap();
-    input
+pub fn generator(input: &str) -> Result<Vec<ValveDescription>, ParseError> {
+    static VALVE: Pattern =
+        Pattern::new(r"^Valve (\w+) has flow rate=(\d+); tunnels? leads? to valves? (.*)$");
+    let mut tunnel_texts = Vec::new();
+    let result = input
         .lines()
         .map(|line| {
-            let captures = pattern.captures(line).unwrap();
-            ValveDescription {
+            let captures = VALVE.captures(
+                input,
+                line,
+                "Valve XX has flow rate=N; tunnels lead to valves XX, YY",
+            )?;
+            let tunnels = list(captures.get(3).unwrap().as_str(), ", ", |tunnel| {
+                tunnel_texts.push(tunnel);
+                Ok(tunnel.to_owned())
+            })?;
+            Ok(ValveDescription {
                 name: captures[1].to_owned(),
-                rate: captures[2].parse().unwrap(),
-                tunnels: captures[3].split(", ").map(|s| s.to_owned()).collect(),
-            }
+                rate: integer(input, &captures[2], "a flow rate")?,
+                tunnels,
+            })
         })
-        .collect()
+        .collect::<Result<Vec<_>, _>>()?;
+    if let Some(unknown) = tunnel_texts
+        .into_iter()
+        .find(|name| !result.iter().any(|valve| valve.name == *name))
+    {
+        return Err(ParseError::new(input, unknown, "a known valve"));
+    }
+    Ok(result)
 }
 
 #[derive(Debug)]
@@ -61,14 +80,13 @@ impl From<&[ValveDescription]> for Mapping {
 
         let routes = (0..tunnels.len())
             .flat_map(|source_idx| {
-                let mut bfs = LinearBfs::<usize>::new(tunnels.len());
                 let mut routes = vec![Vec::new(); tunnels.len()];
-                bfs.add_root(source_idx, 0);
-                while let Some(visited_idx) = bfs.consider_next() {
-                    routes[visited_idx] = bfs.path_to(visited_idx);
-                    for neighbor in tunnels[visited_idx].iter() {
-                        bfs.add_edge(visited_idx, *neighbor, 1);
-                    }
+                let mut walk = LinearBfs::<usize>::new(tunnels.len())
+                    .walk([(source_idx, 0)], |&idx| {
+                        tunnels[idx].iter().map(|&neighbor| (neighbor, 1))
+                    });
+                while let Some(visited_idx) = walk.next_node() {
+                    routes[visited_idx] = walk.search().path_to(&visited_idx);
                 }
                 routes
             })
@@ -85,8 +103,15 @@ impl From<&[ValveDescription]> for Mapping {
 }
 
 enum Step {
-    Pass { pos: usize, goal: usize },
+    Pass {
+        pos: usize,
+        goal: usize,
+    },
     Open(usize),
+    /// Stays in place because every remaining valve is already taken by another participant.
+    /// Without it a participant with nothing to do would end the whole route, and the others
+    /// could never open the last valves.
+    Idle(usize),
 }
 
 impl Step {
@@ -94,6 +119,7 @@ impl Step {
         match self {
             Step::Pass { pos, goal: _ } => *pos,
             Step::Open(idx) => *idx,
+            Step::Idle(idx) => *idx,
         }
     }
 }
@@ -120,7 +146,7 @@ impl Mapping {
         self.route(from, to).len() - 1
     }
 
-    fn start_route(&self, start_from: usize, num_participants: usize) -> Route {
+    fn start_route(&self, start_from: usize, num_participants: usize) -> Route<'_> {
         Route {
             mapping: self,
             start_from,
@@ -132,15 +158,45 @@ impl Mapping {
     }
 }
 
+/// Every way to send the free participants to different valves. When there are fewer valves than
+/// participants, each valve gets one and the rest of the participants have nothing to do.
+fn assignments(
+    valves: &[usize],
+    num_participants: usize,
+) -> impl Iterator<Item = Vec<Option<usize>>> + '_ {
+    if num_participants <= valves.len() {
+        Either::Left(
+            valves
+                .iter()
+                .copied()
+                .map(Some)
+                .permutations(num_participants),
+        )
+    } else {
+        Either::Right(
+            (0..num_participants)
+                .permutations(valves.len())
+                .map(move |participants| {
+                    let mut assignment = vec![None; num_participants];
+                    for (participant, valve) in participants.into_iter().zip(valves) {
+                        assignment[participant] = Some(*valve);
+                    }
+                    assignment
+                }),
+        )
+    }
+}
+
 impl Route<'_> {
-    fn push_step(&mut self, indices: &[usize]) {
+    /// `None` means the participant has nothing left to do and stays where it is.
+    fn push_step(&mut self, indices: &[Option<usize>]) {
         assert!(indices.len() == self.steps.len());
         let min_distance = indices
             .iter()
             .zip(self.steps.iter())
-            .map(|(idx, steps)| {
+            .filter_map(|(idx, steps)| {
                 let curr_idx = steps.last().map(|s| s.idx()).unwrap_or(self.start_from);
-                self.mapping.distance(curr_idx, *idx)
+                Some(self.mapping.distance(curr_idx, (*idx)?))
             })
             .min()
             .unwrap();
@@ -149,6 +205,10 @@ impl Route<'_> {
         self.total_released += action_duration * self.combined_rate;
         for (&idx, steps) in indices.iter().zip(self.steps.iter_mut()) {
             let curr_idx = steps.last().map(|s| s.idx()).unwrap_or(self.start_from);
+            let Some(idx) = idx else {
+                steps.push(Step::Idle(curr_idx));
+                continue;
+            };
             let distance = self.mapping.distance(curr_idx, idx);
             if distance == min_distance {
                 self.combined_rate += self.mapping.rates[idx];
@@ -179,6 +239,7 @@ impl Route<'_> {
                     action_duration = distance + 1;
                     self.combined_rate -= self.mapping.rates[removed_idx];
                 }
+                Step::Idle(_) => {}
             }
         }
         self.time -= action_duration;
@@ -190,15 +251,21 @@ impl Route<'_> {
         self.total_released + remaining_time * self.combined_rate
     }
 
-    fn find_best_under_time_limit(&mut self, time_limit: usize) -> usize {
+    fn find_best_under_time_limit(&mut self, time_limit: usize, meter: &mut Meter) -> usize {
         let mut best = self.total_released_if_continued_until(time_limit);
+        if !meter.spend_node() {
+            return best;
+        }
+        stats::node_expanded();
+        let _depth = stats::descend();
         let mut already_open = self
             .steps
             .iter()
             .flatten()
-            .map(|step| match step {
-                Step::Pass { pos: _, goal } => goal,
-                Step::Open(idx) => idx,
+            .filter_map(|step| match step {
+                Step::Pass { pos: _, goal } => Some(goal),
+                Step::Open(idx) => Some(idx),
+                Step::Idle(_) => None,
             })
             .collect_vec();
         already_open.sort();
@@ -226,24 +293,26 @@ impl Route<'_> {
             .enumerate()
             .map(|(i, steps)| {
                 if let Some(Step::Pass { pos: _, goal }) = steps.last() {
-                    *goal
+                    Some(*goal)
                 } else {
                     indices_for_new_steps.push(i);
-                    0
+                    None
                 }
             })
             .collect_vec();
 
-        for try_indices in left_to_open
-            .into_iter()
-            .permutations(indices_for_new_steps.len())
-        {
+        for try_indices in assignments(&left_to_open, indices_for_new_steps.len()) {
             for (i, try_idx) in indices_for_new_steps.iter().zip(try_indices.iter()) {
                 new_steps_buffer[*i] = *try_idx;
             }
+            if new_steps_buffer.iter().all(Option::is_none) {
+                continue;
+            }
             self.push_step(&new_steps_buffer);
             if self.time < time_limit {
-                best = best.max(self.find_best_under_time_limit(time_limit));
+                best = best.max(self.find_best_under_time_limit(time_limit, meter));
+            } else {
+                stats::pruned();
             }
             self.pop_step();
         }
@@ -251,16 +320,185 @@ impl Route<'_> {
     }
 }
 
-pub fn part_1(input: &[ValveDescription]) -> usize {
-    let mapping = Mapping::from(input);
-    mapping
-        .start_route(mapping.name_to_idx["AA"], 1)
-        .find_best_under_time_limit(30)
+#[derive(Debug, Clone)]
+pub struct Params {
+    pub start_at: String,
+    pub time_limit_alone: usize,
+    pub time_limit_with_elephant: usize,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            start_at: "AA".to_owned(),
+            time_limit_alone: 30,
+            time_limit_with_elephant: 26,
+        }
+    }
 }
 
-pub fn part_2(input: &[ValveDescription]) -> usize {
+fn most_released(
+    input: &[ValveDescription],
+    start_at: &str,
+    num_participants: usize,
+    time_limit: usize,
+    budget: &Budget,
+) -> Result<Outcome<usize>, SolveError> {
     let mapping = Mapping::from(input);
-    mapping
-        .start_route(mapping.name_to_idx["AA"], 2)
-        .find_best_under_time_limit(26)
+    let start_from = *mapping
+        .name_to_idx
+        .get(start_at)
+        .ok_or_else(|| SolveError::MissingStart(format!("valve {}", start_at)))?;
+    let mut meter = budget.start();
+    let best = mapping
+        .start_route(start_from, num_participants)
+        .find_best_under_time_limit(time_limit, &mut meter);
+    Ok(meter.outcome(best))
+}
+
+pub fn part_1(input: &[ValveDescription]) -> Result<usize, SolveError> {
+    part_1_with(input, &Params::default())
+}
+
+pub fn part_1_with(input: &[ValveDescription], params: &Params) -> Result<usize, SolveError> {
+    part_1_within(input, params, &Budget::unlimited()).map(Outcome::value)
+}
+
+pub fn part_1_within(
+    input: &[ValveDescription],
+    params: &Params,
+    budget: &Budget,
+) -> Result<Outcome<usize>, SolveError> {
+    most_released(input, &params.start_at, 1, params.time_limit_alone, budget)
+}
+
+pub fn part_2(input: &[ValveDescription]) -> Result<usize, SolveError> {
+    part_2_with(input, &Params::default())
+}
+
+pub fn part_2_with(input: &[ValveDescription], params: &Params) -> Result<usize, SolveError> {
+    part_2_within(input, params, &Budget::unlimited()).map(Outcome::value)
+}
+
+pub fn part_2_within(
+    input: &[ValveDescription],
+    params: &Params,
+    budget: &Budget,
+) -> Result<Outcome<usize>, SolveError> {
+    most_released(
+        input,
+        &params.start_at,
+        2,
+        params.time_limit_with_elephant,
+        budget,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+Valve AA has flow rate=0; tunnels lead to valves DD, II, BB
+Valve BB has flow rate=13; tunnels lead to valves CC, AA
+Valve CC has flow rate=2; tunnels lead to valves DD, BB
+Valve DD has flow rate=20; tunnels lead to valves CC, AA, EE
+Valve EE has flow rate=3; tunnels lead to valves FF, DD
+Valve FF has flow rate=0; tunnels lead to valves EE, GG
+Valve GG has flow rate=0; tunnels lead to valves FF, HH
+Valve HH has flow rate=22; tunnel leads to valve GG
+Valve II has flow rate=0; tunnels lead to valves AA, JJ
+Valve JJ has flow rate=21; tunnel leads to valve II
+";
+
+    #[test]
+    fn part_1_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part_1(&input), Ok(1651));
+    }
+
+    #[test]
+    fn part_2_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part_2(&input), Ok(1707));
+    }
+
+    #[test]
+    fn distinct_assignments() {
+        let all = |valves: &[usize], num_participants| {
+            assignments(valves, num_participants).collect_vec()
+        };
+        let pairs = all(&[5, 7, 9], 2);
+        assert_eq!(pairs.len(), 6);
+        assert!(pairs.iter().all_unique());
+        assert_eq!(all(&[5], 2), [vec![Some(5), None], vec![None, Some(5)]]);
+        assert_eq!(all(&[], 2), [vec![None, None]]);
+    }
+
+    #[test]
+    fn fewer_valves_than_participants() {
+        let input = generator(
+            "\
+Valve AA has flow rate=0; tunnel leads to valve BB
+Valve BB has flow rate=10; tunnel leads to valve AA
+",
+        )
+        .unwrap();
+        // Whoever is not opening BB has to idle, which must not stop the other one.
+        assert_eq!(part_1(&input), Ok(10 * 28));
+        assert_eq!(part_2(&input), Ok(10 * 24));
+    }
+
+    #[test]
+    fn best_so_far_within_budget() {
+        let input = generator(EXAMPLE).unwrap();
+        let params = Params::default();
+        let nodes = |max_nodes| Budget {
+            max_nodes: Some(max_nodes),
+            ..Budget::default()
+        };
+        assert_eq!(
+            part_1_within(&input, &params, &Budget::unlimited()),
+            Ok(Outcome::Final(1651))
+        );
+        assert_eq!(
+            part_2_within(&input, &params, &nodes(1_000_000)),
+            Ok(Outcome::Final(1707))
+        );
+
+        let Ok(Outcome::BestSoFar(best)) = part_1_within(&input, &params, &nodes(0)) else {
+            panic!()
+        };
+        assert_eq!(best, 0);
+        let Ok(Outcome::BestSoFar(best)) = part_1_within(&input, &params, &nodes(20)) else {
+            panic!()
+        };
+        assert!(0 < best && best <= 1651);
+        let Ok(Outcome::BestSoFar(best)) = part_2_within(&input, &params, &nodes(20)) else {
+            panic!()
+        };
+        assert!(0 < best && best <= 1707);
+
+        let short = Params {
+            time_limit_alone: 10,
+            ..Params::default()
+        };
+        let Ok(Outcome::Final(best)) = part_1_within(&input, &short, &nodes(1_000_000)) else {
+            panic!()
+        };
+        assert!(best < 1651);
+        assert_eq!(part_1_with(&input, &short), Ok(best));
+    }
+
+    #[test]
+    fn missing_start() {
+        let input = generator(EXAMPLE).unwrap();
+        let params = Params {
+            start_at: "ZZ".to_owned(),
+            ..Params::default()
+        };
+        let missing = Err(SolveError::MissingStart("valve ZZ".to_owned()));
+        assert_eq!(part_1_with(&input, &params), missing);
+        assert_eq!(part_2_with(&input, &params), missing);
+    }
 }
@@ -1,26 +1,35 @@
 use std::fmt::{Display, Write};
-use std::iter::{from_fn, repeat};
+use std::iter::repeat_n;
 use std::ops::{Index, IndexMut};
 
 use hashbrown::HashMap;
 use itertools::Itertools;
 
+use crate::error::ParseError;
+use crate::parsing::chars;
+use crate::solution::Part;
+use crate::stats;
+use crate::visualize::{Frame, Rgb, Visualize};
+
 #[derive(Debug, Clone, Copy)]
 pub enum Jet {
     Left,
     Right,
 }
 
-pub fn generator(input: &str) -> Vec<Jet> {
-    input
-        .chars()
-        .filter_map(|c| match c {
-            '<' => Some(Jet::Left),
-            '>' => Some(Jet::Right),
+pub fn generator(input: &str) -> Result<Vec<Jet>, ParseError> {
+    let jets = chars(input)
+        .filter_map(|(c, at)| match c {
+            '<' => Some(Ok(Jet::Left)),
+            '>' => Some(Ok(Jet::Right)),
             '\n' => None,
-            _ => panic!("Bad {:?}", c),
+            _ => Some(Err(ParseError::new(input, at, "< or >"))),
         })
-        .collect()
+        .collect::<Result<Vec<_>, _>>()?;
+    if jets.is_empty() {
+        return Err(ParseError::at_end(input, "at least one jet"));
+    }
+    Ok(jets)
 }
 
 struct BrickPattern {
@@ -106,13 +115,39 @@ impl Arena {
         }
     }
 
-    fn top_rows(&self, num_rows: usize) -> &[bool] {
-        let num_cells = num_rows * self.cols;
-        if self.rocks.len() < num_cells {
-            &self.rocks
-        } else {
-            &self.rocks[self.rocks.len() - num_cells..]
+    /// The cells a falling brick could still get to, from the top row down to the lowest of them,
+    /// with all the other cells marked as rocks. A well along a wall that never gets filled would
+    /// make the surface grow forever, so it only goes down to `SURFACE_DEPTH` rows - which means
+    /// towers that differ deeper down can have the same surface.
+    fn surface(&self) -> Vec<bool> {
+        let rows = self.rows();
+        let bottom = rows.saturating_sub(SURFACE_DEPTH);
+        let mut reachable = vec![false; (rows + 1 - bottom) * self.cols];
+        let mut lowest = rows;
+        let mut to_visit = (0..self.cols).map(|x| [x, rows]).collect_vec();
+        while let Some([x, y]) = to_visit.pop() {
+            let visited = &mut reachable[x + (y - bottom) * self.cols];
+            if *visited || self[[x, y]] {
+                continue;
+            }
+            *visited = true;
+            lowest = lowest.min(y);
+            if 0 < x {
+                to_visit.push([x - 1, y]);
+            }
+            if x + 1 < self.cols {
+                to_visit.push([x + 1, y]);
+            }
+            if bottom < y {
+                to_visit.push([x, y - 1]);
+            }
         }
+        reachable[(lowest - bottom) * self.cols..]
+            .chunks(self.cols)
+            .rev()
+            .flatten()
+            .map(|reachable| !reachable)
+            .collect()
     }
 
     fn has_collision(&self, [x, y]: [usize; 2], contacts: &[[usize; 2]]) -> bool {
@@ -120,6 +155,11 @@ impl Arena {
     }
 }
 
+const SURFACE_DEPTH: usize = 64;
+
+/// How many bricks to drop while looking for the tower to repeat.
+const MAX_BRICKS: usize = 1000000;
+
 struct State {
     arena: Arena,
     current: Option<(&'static BrickPattern, [usize; 2])>,
@@ -169,6 +209,45 @@ impl Display for State {
     }
 }
 
+/// How many rows of the top of the tower are drawn.
+const VISIBLE_ROWS: usize = 40;
+
+impl Visualize for State {
+    fn frame(&self) -> Frame {
+        let mut frame = Frame::new(self.arena.cols + 2, VISIBLE_ROWS, Rgb::BLACK);
+        let falling_top = self
+            .current
+            .map_or(0, |(pattern, [_, y])| y + pattern.pattern.len());
+        // One past the highest row, leaving room for the floor below row 0 at the start.
+        let top = falling_top.max(self.arena.rows()).max(VISIBLE_ROWS - 1);
+        let to_frame_row = |row: usize| top - 1 - row;
+        for frame_row in 0..VISIBLE_ROWS {
+            frame.set([0, frame_row], Rgb::GRAY);
+            frame.set([self.arena.cols + 1, frame_row], Rgb::GRAY);
+        }
+        for x in 0..self.arena.cols + 2 {
+            frame.set([x, top], Rgb::GRAY);
+        }
+        for row in top.saturating_sub(VISIBLE_ROWS)..self.arena.rows() {
+            for (col, rock) in self.arena.row(row).iter().enumerate() {
+                if *rock {
+                    frame.set([col + 1, to_frame_row(row)], Rgb::SAND);
+                }
+            }
+        }
+        if let Some((pattern, [x, y])) = self.current {
+            for (r, pattern_row) in pattern.pattern.iter().enumerate() {
+                for (c, rock) in pattern_row.iter().enumerate() {
+                    if *rock {
+                        frame.set([x + c + 1, to_frame_row(y + r)], Rgb::RED);
+                    }
+                }
+            }
+        }
+        frame
+    }
+}
+
 impl State {
     fn new(cols: usize) -> Self {
         Self {
@@ -190,9 +269,10 @@ impl State {
         let (pattern, [x, y]) = self.current.take().unwrap();
         let rows_required = y + pattern.pattern.len();
         if self.arena.rows() < rows_required {
-            self.arena
-                .rocks
-                .extend(repeat(false).take(self.arena.cols * (rows_required - self.arena.rows())));
+            self.arena.rocks.extend(repeat_n(
+                false,
+                self.arena.cols * (rows_required - self.arena.rows()),
+            ));
         }
         for (r, brick_row) in pattern.pattern.iter().enumerate() {
             for (c, rock) in brick_row.iter().enumerate() {
@@ -238,104 +318,262 @@ impl State {
     }
 }
 
-pub fn solve_for(input: &[Jet], total_bricks: usize) -> usize {
-    type Key = (usize, usize, Vec<bool>);
+#[derive(Debug, Clone)]
+pub struct Params {
+    pub width: usize,
+}
 
-    #[derive(Debug, Clone)]
-    struct Step {
-        added_rows: usize,
-        got_to_key: Key,
+impl Default for Params {
+    fn default() -> Self {
+        Self { width: 7 }
     }
+}
 
-    let mut state = State::new(7);
-    let initial_key = (0, 0, state.arena.top_rows(0).to_owned());
+pub fn solve_for(input: &[Jet], total_bricks: usize, params: &Params) -> usize {
+    solve_observed(input, total_bricks, params, &mut |_| {})
+}
+
+fn solve_observed(
+    input: &[Jet],
+    total_bricks: usize,
+    params: &Params,
+    observe: &mut dyn FnMut(&dyn Visualize),
+) -> usize {
+    type Key = (usize, usize, Vec<bool>);
+
+    let mut state = State::new(params.width);
     let mut jets = input.iter().enumerate().cycle().peekable();
-    let mut steps = HashMap::<Key, Step>::new();
-    let mut step_to_add = None;
-    let cycle_starts_at = BRICK_PATTERNS
+    // The key before each brick fell, and how many rows the brick added.
+    let mut history = Vec::<(Key, usize)>::new();
+    let mut last_seen = HashMap::<Key, usize>::new();
+    for (brick_num, brick_pattern) in BRICK_PATTERNS
         .iter()
         .enumerate()
         .cycle()
-        .take(1000000)
-        .find_map(|(brick_num, brick_pattern)| {
-            let first_jet_num = jets.peek().unwrap().0;
-            let key = (brick_num, first_jet_num, state.arena.top_rows(1).to_owned());
-            if let Some((prev_key, added_rows)) = step_to_add.take() {
-                steps.insert(
-                    prev_key,
-                    Step {
-                        added_rows,
-                        got_to_key: key.clone(),
-                    },
-                );
+        .take(total_bricks.min(MAX_BRICKS))
+    {
+        let first_jet_num = jets.peek().unwrap().0;
+        let key = (brick_num, first_jet_num, state.arena.surface());
+        let now = history.len();
+        if let Some(previous) = last_seen.insert(key.clone(), now) {
+            stats::memo_hit();
+            // The key does not see the whole tower, so only trust the cycle once a whole period
+            // went exactly like the one before it.
+            let period = now - previous;
+            if period <= previous && history[previous - period..previous] == history[previous..now]
+            {
+                return extrapolate(&history, previous - period, period, total_bricks);
             }
-            if steps.contains_key(&key) {
-                return Some(key);
+        }
+        stats::node_expanded();
+
+        state.set_brick(brick_pattern, [2, state.arena.rows() + 3]);
+        observe(&state);
+        for (_, jet) in jets.by_ref() {
+            state.push_brick(*jet);
+            let could_fall = state.drop_brick();
+            observe(&state);
+            if !could_fall {
+                break;
             }
+        }
+        let rows_before = state.arena.rows();
+        state.freeze_brick();
+        history.push((key, state.arena.rows() - rows_before));
+    }
+    // All the bricks fell before the tower started repeating.
+    assert!(
+        total_bricks <= MAX_BRICKS,
+        "no cycle in {} bricks",
+        MAX_BRICKS
+    );
+    state.arena.rows()
+}
 
-            state.set_brick(brick_pattern, [2, state.arena.rows() + 3]);
-            for (_, jet) in jets.by_ref() {
-                state.push_brick(*jet);
-                let could_fall = state.drop_brick();
-                if !could_fall {
-                    break;
-                }
-            }
-            let rows_before = state.arena.rows();
-            state.freeze_brick();
-            let added_rows = state.arena.rows() - rows_before;
+/// The height after `total_bricks`, when the rows each brick adds repeat every `period` bricks
+/// from `cycle_start` on.
+fn extrapolate<K>(
+    history: &[(K, usize)],
+    cycle_start: usize,
+    period: usize,
+    total_bricks: usize,
+) -> usize {
+    let added_rows = |steps: &[(K, usize)]| steps.iter().map(|(_, rows)| rows).sum::<usize>();
+    let cycle = &history[cycle_start..cycle_start + period];
+    let bricks_in_cycles = total_bricks - cycle_start;
+    added_rows(&history[..cycle_start])
+        + bricks_in_cycles / period * added_rows(cycle)
+        + added_rows(&cycle[..bricks_in_cycles % period])
+}
 
-            step_to_add = Some((key, added_rows));
-            None
-        })
-        .unwrap();
-
-    let run_steps_from = |mut key: Key| {
-        let steps = &steps;
-        from_fn(move || {
-            let step = steps[&key].clone();
-            key = step.got_to_key.clone();
-            Some(step)
-        })
-    };
+pub fn part_1(input: &[Jet]) -> usize {
+    part_1_with(input, &Params::default())
+}
 
-    let calc_upto_cycle = |key: Key| {
-        let mut num_rows = 0;
-        for (
-            num_bricks,
-            Step {
-                added_rows,
-                got_to_key,
-            },
-        ) in run_steps_from(key).enumerate()
-        {
-            num_rows += added_rows;
-            if got_to_key == cycle_starts_at {
-                return (num_bricks + 1, num_rows);
-            }
-        }
-        panic!()
+pub fn part_1_with(input: &[Jet], params: &Params) -> usize {
+    solve_for(input, 2022, params)
+}
+
+pub fn part_2(input: &[Jet]) -> usize {
+    part_2_with(input, &Params::default())
+}
+
+pub fn part_2_with(input: &[Jet], params: &Params) -> usize {
+    solve_for(input, 1_000_000_000_000, params)
+}
+
+/// Shows the top of the tower while bricks fall, until the heights start repeating. The parts
+/// only differ in how many bricks they count, so they look the same.
+pub fn visualize(input: &[Jet], part: Part, observe: &mut dyn FnMut(&dyn Visualize)) {
+    let total_bricks = match part {
+        Part::One => 2022,
+        Part::Two => 1_000_000_000_000,
     };
+    solve_observed(input, total_bricks, &Params::default(), observe);
+}
 
-    let (one_time_bricks, one_time_rows) = calc_upto_cycle(initial_key);
-    let (per_cycle_bricks, per_cycle_rows) = calc_upto_cycle(cycle_starts_at.clone());
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let bricks_added_in_cycles = total_bricks - one_time_bricks;
-    let num_cycles = bricks_added_in_cycles / per_cycle_bricks;
+    const EXAMPLE: &str = "\
+>>><<><>><<<>><>>><<<>>><<<><<<>><>><<>>
+";
 
-    let bricks_added_in_partial_cycle = bricks_added_in_cycles % per_cycle_bricks;
-    let rows_added_in_partial_cycle: usize = run_steps_from(cycle_starts_at)
-        .take(bricks_added_in_partial_cycle)
-        .map(|step| step.added_rows)
-        .sum();
+    #[test]
+    fn part_1_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part_1(&input), 3068);
+    }
 
-    one_time_rows + num_cycles * per_cycle_rows + rows_added_in_partial_cycle
-}
+    #[test]
+    fn part_2_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part_2(&input), 1514285714288);
+    }
 
-pub fn part_1(input: &[Jet]) -> usize {
-    solve_for(input, 2022)
-}
+    /// Drops every single brick, keeping the rocks as a set of cells.
+    fn reference_height(jets: &[Jet], total_bricks: usize, width: usize) -> usize {
+        let shapes: [&[[usize; 2]]; 5] = [
+            &[[0, 0], [1, 0], [2, 0], [3, 0]],
+            &[[1, 0], [0, 1], [1, 1], [2, 1], [1, 2]],
+            &[[0, 0], [1, 0], [2, 0], [2, 1], [2, 2]],
+            &[[0, 0], [0, 1], [0, 2], [0, 3]],
+            &[[0, 0], [1, 0], [0, 1], [1, 1]],
+        ];
+        let mut rocks = hashbrown::HashSet::<[usize; 2]>::new();
+        let mut height = 0usize;
+        let mut jets = jets.iter().cycle();
+        for shape in shapes.into_iter().cycle().take(total_bricks) {
+            let fits = |[x, y]: [usize; 2]| {
+                shape
+                    .iter()
+                    .all(|[dx, dy]| x + dx < width && !rocks.contains(&[x + dx, y + dy]))
+            };
+            let mut pos = [2, height + 3];
+            loop {
+                let pushed_x = match jets.next().unwrap() {
+                    Jet::Left => pos[0].checked_sub(1),
+                    Jet::Right => Some(pos[0] + 1),
+                };
+                if let Some(x) = pushed_x.filter(|&x| fits([x, pos[1]])) {
+                    pos[0] = x;
+                }
+                if 0 < pos[1] && fits([pos[0], pos[1] - 1]) {
+                    pos[1] -= 1;
+                } else {
+                    break;
+                }
+            }
+            for [dx, dy] in shape {
+                rocks.insert([pos[0] + dx, pos[1] + dy]);
+                height = height.max(pos[1] + dy + 1);
+            }
+        }
+        height
+    }
 
-pub fn part_2(input: &[Jet]) -> usize {
-    solve_for(input, 1_000_000_000_000)
+    #[test]
+    fn matches_reference_on_random_inputs() {
+        let params = Params::default();
+        for seed in 0..10 {
+            let text = crate::random_input::generate(17, seed, 5 + seed as usize).unwrap();
+            let input = generator(&text).unwrap();
+            for total_bricks in [1, 7, 100, 2022] {
+                assert_eq!(
+                    solve_for(&input, total_bricks, &params),
+                    reference_height(&input, total_bricks, params.width),
+                    "{} bricks with jets {}",
+                    total_bricks,
+                    text.trim()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn fewer_bricks_than_the_cycle_prefix() {
+        let input = generator(EXAMPLE).unwrap();
+        let params = Params::default();
+        for total_bricks in 0..60 {
+            assert_eq!(
+                solve_for(&input, total_bricks, &params),
+                reference_height(&input, total_bricks, params.width),
+                "{} bricks",
+                total_bricks
+            );
+        }
+    }
+
+    #[test]
+    fn well_that_never_fills() {
+        // Nothing is ever pushed against the left wall, so the surface is always cut off.
+        let input = generator(">>>>>>>>>>>>>>>>>>>>>>\n").unwrap();
+        let params = Params::default();
+        assert_eq!(
+            solve_for(&input, 5000, &params),
+            reference_height(&input, 5000, params.width)
+        );
+        // Too many to drop one by one, so this only works if the cycle is found.
+        assert!(0 < solve_for(&input, 1_000_000_000_000, &params));
+    }
+
+    #[test]
+    fn state_display() {
+        let input = generator(EXAMPLE).unwrap();
+        let mut state = State::new(Params::default().width);
+        let mut jets = input.iter().cycle();
+        for brick_pattern in BRICK_PATTERNS.iter().cycle().take(10) {
+            state.set_brick(brick_pattern, [2, state.arena.rows() + 3]);
+            for jet in jets.by_ref() {
+                state.push_brick(*jet);
+                if !state.drop_brick() {
+                    break;
+                }
+            }
+            state.freeze_brick();
+        }
+        assert_eq!(
+            state.to_string(),
+            "\n\
+             +....#..+\n\
+             +....#..+\n\
+             +....##.+\n\
+             +##..##.+\n\
+             +######.+\n\
+             +.###...+\n\
+             +..#....+\n\
+             +.####..+\n\
+             +....##.+\n\
+             +....##.+\n\
+             +....#..+\n\
+             +..#.#..+\n\
+             +..#.#..+\n\
+             +#####..+\n\
+             +..###..+\n\
+             +...#...+\n\
+             +..####.+\n\
+             +-------+"
+        );
+    }
 }
@@ -1,22 +1,21 @@
-use std::iter::from_fn;
 use std::ops::RangeInclusive;
 
 use hashbrown::HashSet;
 use itertools::Itertools;
 
-use crate::bfs::HashMapBfs;
+use crate::bfs::BitSetBfs;
+use crate::error::{ParseError, SolveError};
+use crate::parsing::integers;
 
 type Coord = [isize; 3];
 
-pub fn generator(input: &str) -> Vec<Coord> {
+pub fn generator(input: &str) -> Result<Vec<Coord>, ParseError> {
     input
         .lines()
         .map(|line| {
-            line.split(',')
-                .map(|s| s.parse().unwrap())
-                .collect_vec()
+            integers(input, line, ",", "a number")?
                 .try_into()
-                .unwrap()
+                .map_err(|_| ParseError::new(input, line, "three comma separated numbers"))
         })
         .collect()
 }
@@ -26,22 +25,6 @@ struct CubeMap {
     limits: [RangeInclusive<isize>; 3],
 }
 
-impl FromIterator<Coord> for CubeMap {
-    fn from_iter<T: IntoIterator<Item = Coord>>(iter: T) -> Self {
-        let cubes: HashSet<Coord> = iter.into_iter().collect();
-        let limits = (0..3)
-            .map(|i| match cubes.iter().map(|cube| cube[i]).minmax() {
-                itertools::MinMaxResult::NoElements => panic!(),
-                itertools::MinMaxResult::OneElement(c) => (c - 1)..=(c + 2),
-                itertools::MinMaxResult::MinMax(mn, mx) => (mn - 1)..=(mx + 1),
-            })
-            .collect_vec()
-            .try_into()
-            .unwrap();
-        Self { cubes, limits }
-    }
-}
-
 fn neighbors(coord: Coord) -> impl Iterator<Item = Coord> {
     [
         [-1, 0, 0],
@@ -62,6 +45,20 @@ fn neighbors(coord: Coord) -> impl Iterator<Item = Coord> {
 }
 
 impl CubeMap {
+    fn new(cubes: &[Coord]) -> Result<Self, SolveError> {
+        let cubes: HashSet<Coord> = cubes.iter().copied().collect();
+        let limits = (0..3)
+            .map(|i| match cubes.iter().map(|cube| cube[i]).minmax() {
+                itertools::MinMaxResult::NoElements => Err(SolveError::Empty("cubes".to_owned())),
+                itertools::MinMaxResult::OneElement(c) => Ok((c - 1)..=(c + 2)),
+                itertools::MinMaxResult::MinMax(mn, mx) => Ok((mn - 1)..=(mx + 1)),
+            })
+            .collect::<Result<Vec<_>, _>>()?
+            .try_into()
+            .unwrap();
+        Ok(Self { cubes, limits })
+    }
+
     fn count_surface_area(&self) -> usize {
         self.cubes
             .iter()
@@ -79,35 +76,55 @@ impl CubeMap {
             .sum()
     }
 
+    /// The position of `coord` inside the limits box, if it is inside it.
+    fn index(&self, coord: Coord) -> Option<usize> {
+        self.limits
+            .iter()
+            .zip(coord)
+            .try_fold(0, |index, (limit, c)| {
+                let size = (limit.end() - limit.start() + 1) as usize;
+                limit
+                    .contains(&c)
+                    .then(|| index * size + (c - limit.start()) as usize)
+            })
+    }
+
+    fn coord(&self, mut index: usize) -> Coord {
+        let mut coord = [0; 3];
+        for (c, limit) in coord.iter_mut().zip(&self.limits).rev() {
+            let size = (limit.end() - limit.start() + 1) as usize;
+            *c = limit.start() + (index % size) as isize;
+            index /= size;
+        }
+        coord
+    }
+
+    fn volume(&self) -> usize {
+        self.limits
+            .iter()
+            .map(|limit| (limit.end() - limit.start() + 1) as usize)
+            .product()
+    }
+
     fn flood_fill(&self, start: Coord) -> impl '_ + Iterator<Item = Coord> {
-        let mut bfs = HashMapBfs::<Coord, u8>::new();
-        bfs.add_root(start, 0);
-        from_fn(move || {
-            bfs.consider_next().map(|coord| {
-                for neighbor in neighbors(coord) {
-                    if !self.cubes.contains(&neighbor)
-                        && self
-                            .limits
-                            .iter()
-                            .zip(neighbor)
-                            .all(|(l, n)| l.contains(&n))
-                    {
-                        bfs.add_edge(coord, neighbor, 0);
-                    }
-                }
-                coord
+        BitSetBfs::new(self.volume())
+            .walk([(self.index(start).unwrap(), ())], |&index| {
+                neighbors(self.coord(index))
+                    .filter(|neighbor| !self.cubes.contains(neighbor))
+                    .filter_map(|neighbor| Some((self.index(neighbor)?, ())))
             })
-        })
+            .reachable_set()
+            .into_iter()
+            .map(|index| self.coord(index))
     }
 }
 
-pub fn part_1(input: &[Coord]) -> usize {
-    let cubes_map = input.iter().copied().collect::<CubeMap>();
-    cubes_map.count_surface_area()
+pub fn part_1(input: &[Coord]) -> Result<usize, SolveError> {
+    Ok(CubeMap::new(input)?.count_surface_area())
 }
 
-pub fn part_2(input: &[Coord]) -> usize {
-    let mut cubes_map = input.iter().copied().collect::<CubeMap>();
+pub fn part_2(input: &[Coord]) -> Result<usize, SolveError> {
+    let mut cubes_map = CubeMap::new(input)?;
     let mut non_interior = cubes_map
         .flood_fill(cubes_map.limits.clone().map(|l| *l.start()))
         .collect::<HashSet<Coord>>();
@@ -127,5 +144,45 @@ pub fn part_2(input: &[Coord]) -> usize {
                 }
             }),
     );
-    cubes_map.count_surface_area()
+    Ok(cubes_map.count_surface_area())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+2,2,2
+1,2,2
+3,2,2
+2,1,2
+2,3,2
+2,2,1
+2,2,3
+2,2,4
+2,2,6
+1,2,5
+3,2,5
+2,1,5
+2,3,5
+";
+
+    #[test]
+    fn part_1_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part_1(&input), Ok(64));
+    }
+
+    #[test]
+    fn part_2_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part_2(&input), Ok(58));
+    }
+
+    #[test]
+    fn empty_input() {
+        let input = generator("").unwrap();
+        let empty = Err(SolveError::Empty("cubes".to_owned()));
+        assert_eq!((part_1(&input), part_2(&input)), (empty.clone(), empty));
+    }
 }
@@ -1,5 +1,10 @@
 use std::str::FromStr;
 
+use crate::budget::{Budget, Meter, Outcome};
+use crate::error::{parse_part, ParseError};
+use crate::parsing::{integer, pair, Pattern};
+use crate::stats;
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 enum Material {
     Ore,
@@ -48,25 +53,38 @@ pub struct Blueprint {
     robots_costs: [[usize; 3]; 4],
 }
 
-pub fn generator(input: &str) -> Vec<Blueprint> {
-    let pattern = regex::Regex::new(r#"Each (\w+) robot costs (.*?)\."#).unwrap();
-    input
-        .split("Blueprint ")
-        .filter_map(|text| {
-            let (blueprint_id, blueprint_text) = text.split_once(':')?;
+pub fn generator(input: &str) -> Result<Vec<Blueprint>, ParseError> {
+    static ROBOT: Pattern = Pattern::new(r"Each (\w+) robot costs (.*?)\.");
+    let mut chunks = input.split("Blueprint ");
+    if let Some(before_first) = chunks.next() {
+        if !before_first.trim().is_empty() {
+            return Err(ParseError::new(input, before_first, "Blueprint"));
+        }
+    }
+    chunks
+        .map(|text| {
+            let (blueprint_id, blueprint_text) = pair(input, text, ":", "Blueprint N: ...")?;
             let mut robots_costs = [[0usize; 3]; 4];
-            for captures in pattern.captures_iter(blueprint_text) {
-                let robot_type: Material = captures[1].parse().unwrap();
+            for captures in ROBOT.regex().captures_iter(blueprint_text) {
+                let robot_type: Material = parse_part(input, &captures[1], "a material")?;
                 let costs = &mut robots_costs[robot_type.index()];
-                for cost_description in captures[2].split(" and ") {
-                    let (num, material) = cost_description.split_once(' ').unwrap();
-                    let num: usize = num.parse().unwrap();
-                    let material: Material = material.parse().unwrap();
+                for cost_description in captures.get(2).unwrap().as_str().split(" and ") {
+                    let (num, material_text) = pair(input, cost_description, " ", "N material")?;
+                    let num: usize = integer(input, num, "a number")?;
+                    let material: Material =
+                        parse_part(input, material_text, "ore, clay or obsidian")?;
+                    if material == Material::Geode {
+                        return Err(ParseError::new(
+                            input,
+                            material_text,
+                            "ore, clay or obsidian",
+                        ));
+                    }
                     costs[material.index()] = num;
                 }
             }
-            Some(Blueprint {
-                id: blueprint_id.parse().unwrap(),
+            Ok(Blueprint {
+                id: integer(input, blueprint_id, "a blueprint id")?,
                 robots_costs,
             })
         })
@@ -89,8 +107,13 @@ impl Default for State {
 }
 
 impl State {
-    fn most_geode(&self, blueprint: &Blueprint, remainint_time: usize) -> usize {
+    fn most_geode(&self, blueprint: &Blueprint, remainint_time: usize, meter: &mut Meter) -> usize {
         let mut best = self.clone().wait_minutes(remainint_time).ores[Material::Geode.index()];
+        if !meter.spend_node() {
+            return best;
+        }
+        stats::node_expanded();
+        let _depth = stats::descend();
         for material in Material::all().into_iter() {
             let Some(time_required) = self.time_to_build(blueprint, material) else { break; };
             let time_after_built = time_required + 1;
@@ -99,10 +122,13 @@ impl State {
                     .clone()
                     .wait_minutes(time_after_built)
                     .build_robot(blueprint, material);
-                best = best.max(
-                    after_waiting_and_building
-                        .most_geode(blueprint, remainint_time - time_after_built),
-                );
+                best = best.max(after_waiting_and_building.most_geode(
+                    blueprint,
+                    remainint_time - time_after_built,
+                    meter,
+                ));
+            } else {
+                stats::pruned();
             }
         }
         best
@@ -147,16 +173,87 @@ impl State {
 }
 
 pub fn part_1(input: &[Blueprint]) -> usize {
-    input
+    part_1_within(input, &Budget::unlimited()).value()
+}
+
+/// The budget is shared by all the blueprints.
+pub fn part_1_within(input: &[Blueprint], budget: &Budget) -> Outcome<usize> {
+    let mut meter = budget.start();
+    let quality_levels = input
         .iter()
-        .map(|blueprint| blueprint.id * State::default().most_geode(blueprint, 24))
-        .sum()
+        .map(|blueprint| blueprint.id * State::default().most_geode(blueprint, 24, &mut meter))
+        .sum();
+    meter.outcome(quality_levels)
 }
 
 pub fn part_2(input: &[Blueprint]) -> usize {
-    input
+    part_2_within(input, &Budget::unlimited()).value()
+}
+
+/// The budget is shared by all the blueprints.
+pub fn part_2_within(input: &[Blueprint], budget: &Budget) -> Outcome<usize> {
+    let mut meter = budget.start();
+    let product = input
         .iter()
         .take(3)
-        .map(|blueprint| State::default().most_geode(blueprint, 32))
-        .product()
+        .map(|blueprint| State::default().most_geode(blueprint, 32, &mut meter))
+        .product();
+    meter.outcome(product)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+Blueprint 1: Each ore robot costs 4 ore. Each clay robot costs 2 ore. Each obsidian robot costs 3 ore and 14 clay. Each geode robot costs 2 ore and 7 obsidian.
+Blueprint 2: Each ore robot costs 2 ore. Each clay robot costs 3 ore. Each obsidian robot costs 3 ore and 8 clay. Each geode robot costs 3 ore and 12 obsidian.
+";
+
+    #[test]
+    #[ignore = "takes more than ten minutes even with --release"]
+    fn part_1_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part_1(&input), 33);
+    }
+
+    #[test]
+    #[ignore = "takes more than ten minutes even with --release"]
+    fn part_2_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part_2(&input), 3472);
+    }
+
+    /// The full examples are too slow to run, so search them with less time. In the puzzle's
+    /// walkthrough blueprint 1 builds geode robots after 18 and 21 minutes.
+    #[test]
+    fn short_time_limits() {
+        let input = generator(EXAMPLE).unwrap();
+        let most_geode = |blueprint, minutes| {
+            State::default().most_geode(blueprint, minutes, &mut Budget::unlimited().start())
+        };
+        assert_eq!(most_geode(&input[0], 18), 0);
+        assert_eq!(most_geode(&input[0], 20), 2);
+        assert_eq!(most_geode(&input[0], 21), 3);
+        assert_eq!(most_geode(&input[1], 19), 1);
+    }
+
+    #[test]
+    fn best_so_far_within_budget() {
+        let input = generator(EXAMPLE).unwrap();
+        let nodes = |max_nodes| Budget {
+            max_nodes: Some(max_nodes),
+            ..Budget::default()
+        };
+        let Outcome::BestSoFar(best) = part_1_within(&input, &nodes(10_000)) else {
+            panic!()
+        };
+        assert!(best <= 33);
+        let Outcome::BestSoFar(best) = part_2_within(&input, &nodes(10_000)) else {
+            panic!()
+        };
+        assert!(best <= 3472);
+
+        assert_eq!(part_1_within(&input, &nodes(1)), Outcome::BestSoFar(0));
+    }
 }
@@ -1,3 +1,6 @@
+use crate::error::{parse_part, ParseError};
+use crate::parsing::pair;
+
 #[derive(Debug, Clone, Copy)]
 enum Shape {
     Rock,
@@ -5,13 +8,15 @@ enum Shape {
     Scissors,
 }
 
-impl From<char> for Shape {
-    fn from(c: char) -> Self {
+impl TryFrom<char> for Shape {
+    type Error = char;
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
         match c {
-            'A' | 'X' => Self::Rock,
-            'B' | 'Y' => Self::Paper,
-            'C' | 'Z' => Self::Scissors,
-            _ => panic!("Bad command {:?}", c),
+            'A' | 'X' => Ok(Self::Rock),
+            'B' | 'Y' => Ok(Self::Paper),
+            'C' | 'Z' => Ok(Self::Scissors),
+            _ => Err(c),
         }
     }
 }
@@ -23,13 +28,15 @@ enum RoundResult {
     Win,
 }
 
-impl From<char> for RoundResult {
-    fn from(c: char) -> Self {
+impl TryFrom<char> for RoundResult {
+    type Error = char;
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
         match c {
-            'X' => Self::Lose,
-            'Y' => Self::Draw,
-            'Z' => Self::Win,
-            _ => panic!("Bad command {:?}", c),
+            'X' => Ok(Self::Lose),
+            'Y' => Ok(Self::Draw),
+            'Z' => Ok(Self::Win),
+            _ => Err(c),
         }
     }
 }
@@ -106,19 +113,22 @@ impl RoundStrategy {
     }
 }
 
-pub fn generator(input: &str) -> Vec<RoundStrategy> {
+pub fn generator(input: &str) -> Result<Vec<RoundStrategy>, ParseError> {
     input
         .lines()
         .map(|line| {
-            let mut it = line.chars();
-            let opponent = it.next().unwrap().into();
-            it.next().unwrap();
-            let other_param = it.next().unwrap();
-            RoundStrategy {
+            let (opponent_text, other_param_text) =
+                pair(input, line, " ", "two space separated columns")?;
+            let opponent = parse_part::<char>(input, opponent_text, "A, B or C")?
+                .try_into()
+                .map_err(|_| ParseError::new(input, opponent_text, "A, B or C"))?;
+            let other_param = parse_part::<char>(input, other_param_text, "X, Y or Z")?;
+            let bad_other_param = |_| ParseError::new(input, other_param_text, "X, Y or Z");
+            Ok(RoundStrategy {
                 opponent,
-                your: other_param.into(),
-                desired: other_param.into(),
-            }
+                your: other_param.try_into().map_err(bad_other_param)?,
+                desired: other_param.try_into().map_err(bad_other_param)?,
+            })
         })
         .collect()
 }
@@ -130,3 +140,26 @@ pub fn part_1(input: &[RoundStrategy]) -> usize {
 pub fn part_2(input: &[RoundStrategy]) -> usize {
     input.iter().map(|r| r.score2()).sum()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+A Y
+B X
+C Z
+";
+
+    #[test]
+    fn part_1_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part_1(&input), 15);
+    }
+
+    #[test]
+    fn part_2_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part_2(&input), 12);
+    }
+}
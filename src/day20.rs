@@ -2,8 +2,14 @@ use std::cmp::Ordering;
 
 use itertools::Itertools;
 
-pub fn generator(input: &str) -> Vec<isize> {
-    input.lines().map(|line| line.parse().unwrap()).collect()
+use crate::error::{ParseError, SolveError};
+use crate::parsing::integer;
+
+pub fn generator(input: &str) -> Result<Vec<isize>, ParseError> {
+    input
+        .lines()
+        .map(|line| integer(input, line, "a number"))
+        .collect()
 }
 
 fn mix_indices(indices: &mut Vec<usize>, numbers: &[isize]) {
@@ -27,21 +33,24 @@ fn mix_indices(indices: &mut Vec<usize>, numbers: &[isize]) {
     }
 }
 
-fn extract_answer(indices: &[usize], numbers: &[isize]) -> isize {
-    let zero_pos = indices.iter().position(|idx| numbers[*idx] == 0).unwrap();
-    [1000, 2000, 3000]
+fn extract_answer(indices: &[usize], numbers: &[isize]) -> Result<isize, SolveError> {
+    let zero_pos = indices
+        .iter()
+        .position(|idx| numbers[*idx] == 0)
+        .ok_or_else(|| SolveError::MissingStart("number 0".to_owned()))?;
+    Ok([1000, 2000, 3000]
         .into_iter()
         .map(|i| numbers[indices[(zero_pos + i) % indices.len()]])
-        .sum()
+        .sum())
 }
 
-pub fn part_1(input: &[isize]) -> isize {
+pub fn part_1(input: &[isize]) -> Result<isize, SolveError> {
     let mut indices = (0..input.len()).collect_vec();
     mix_indices(&mut indices, input);
     extract_answer(&indices, input)
 }
 
-pub fn part_2(input: &[isize]) -> isize {
+pub fn part_2(input: &[isize]) -> Result<isize, SolveError> {
     let new_numbers = input.iter().map(|num| num * 811589153).collect_vec();
     let mut indices = (0..input.len()).collect_vec();
     for _ in 0..10 {
@@ -49,3 +58,38 @@ pub fn part_2(input: &[isize]) -> isize {
     }
     extract_answer(&indices, &new_numbers)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+1
+2
+-3
+3
+-2
+0
+4
+";
+
+    #[test]
+    fn part_1_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part_1(&input), Ok(3));
+    }
+
+    #[test]
+    fn part_2_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part_2(&input), Ok(1623178306));
+    }
+
+    #[test]
+    fn no_zero() {
+        let input = generator("1\n2\n-3\n").unwrap();
+        let missing = Err(SolveError::MissingStart("number 0".to_owned()));
+        assert_eq!(part_1(&input), missing);
+        assert_eq!(part_2(&input), missing);
+    }
+}
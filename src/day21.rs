@@ -2,10 +2,12 @@ use std::cmp::Reverse;
 use std::fmt::{Debug, Display, Write};
 use std::str::FromStr;
 
-use hashbrown::HashMap;
+use hashbrown::{HashMap, HashSet};
 use itertools::Itertools;
 
 use crate::bfs::HashMapBfs;
+use crate::error::{parse_part, ParseError, SolveError};
+use crate::parsing::{integer, Pattern};
 
 #[derive(Clone, Copy, Hash, PartialEq, Eq)]
 pub struct MonkeyName([char; 4]);
@@ -61,33 +63,51 @@ impl MonkeyYell {
             MonkeyYell::Add(monkey1, monkey2) => Some(get_dlg(*monkey1)? + get_dlg(*monkey2)?),
             MonkeyYell::Sub(monkey1, monkey2) => Some(get_dlg(*monkey1)? - get_dlg(*monkey2)?),
             MonkeyYell::Mul(monkey1, monkey2) => Some(get_dlg(*monkey1)? * get_dlg(*monkey2)?),
-            MonkeyYell::Div(monkey1, monkey2) => Some(get_dlg(*monkey1)? / get_dlg(*monkey2)?),
+            MonkeyYell::Div(monkey1, monkey2) => get_dlg(*monkey1)?.checked_div(get_dlg(*monkey2)?),
         }
     }
 }
 
-pub fn generator(input: &str) -> Vec<RowData> {
-    let pattern = regex::Regex::new(r#"(\w+): (?:(\d+)|(\w+) ([+\-*/]) (\w+))$"#).unwrap();
-    input
+pub fn generator(input: &str) -> Result<Vec<RowData>, ParseError> {
+    static MONKEY: Pattern = Pattern::new(r"^(\w+): (?:(\d+)|(\w+) ([+\-*/]) (\w+))$");
+    let mut dependencies = Vec::new();
+    let result = input
         .lines()
         .map(|line| {
-            let captures = pattern.captures(line).unwrap();
-            let monkey_name: MonkeyName = captures[1].parse().unwrap();
+            let captures = MONKEY.captures(
+                input,
+                line,
+                "a monkey name followed by a number or an operation",
+            )?;
+            let monkey_name = |i: usize| {
+                parse_part::<MonkeyName>(input, &captures[i], "a four letters monkey name")
+            };
             let monkey_yell = if let Some(number) = captures.get(2) {
-                MonkeyYell::Number(number.as_str().parse().unwrap())
+                MonkeyYell::Number(integer(input, number.as_str(), "a number")?)
             } else {
-                let [monkey1, monkey2] = [3, 5].map(|i| captures[i].parse::<MonkeyName>().unwrap());
+                let (monkey1, monkey2) = (monkey_name(3)?, monkey_name(5)?);
+                for (monkey, i) in [(monkey1, 3), (monkey2, 5)] {
+                    dependencies.push((monkey, captures.get(i).unwrap().as_str()));
+                }
                 match &captures[4] {
                     "+" => MonkeyYell::Add(monkey1, monkey2),
                     "-" => MonkeyYell::Sub(monkey1, monkey2),
                     "*" => MonkeyYell::Mul(monkey1, monkey2),
                     "/" => MonkeyYell::Div(monkey1, monkey2),
-                    _ => panic!(),
+                    _ => unreachable!("the pattern only allows + - * /"),
                 }
             };
-            (monkey_name, monkey_yell)
+            Ok((monkey_name(1)?, monkey_yell))
         })
-        .collect()
+        .collect::<Result<Vec<_>, _>>()?;
+    let defined: HashSet<MonkeyName> = result.iter().map(|(name, _)| *name).collect();
+    if let Some((_, unknown)) = dependencies
+        .into_iter()
+        .find(|(monkey, _)| !defined.contains(monkey))
+    {
+        return Err(ParseError::new(input, unknown, "a known monkey"));
+    }
+    Ok(result)
 }
 
 fn get_concrete_numbers_ignoring(
@@ -95,26 +115,27 @@ fn get_concrete_numbers_ignoring(
     root: MonkeyName,
     mut ignore_pred: impl FnMut(MonkeyName) -> bool,
 ) -> HashMap<MonkeyName, isize> {
-    let mut bfs = HashMapBfs::<MonkeyName, usize>::new();
-    bfs.add_root(root, 0);
-    while let Some(monkey) = bfs.consider_next() {
-        let yell = &monkey_map[&monkey];
-        for dependency in yell.dependencies().into_iter().flatten() {
-            bfs.add_edge(monkey, dependency, 1);
-        }
-    }
+    let bfs = HashMapBfs::<MonkeyName, usize>::new()
+        .walk([(root, 0)], |monkey| {
+            monkey_map[monkey]
+                .dependencies()
+                .into_iter()
+                .flatten()
+                .map(|dependency| (dependency, 1))
+        })
+        .run();
     let mut concrete_numbers = HashMap::new();
     let by_order = bfs
         .all_known()
         .sorted_by_key(|monkey_name| Reverse(bfs.cost(monkey_name)));
     for monkey in by_order {
-        if ignore_pred(*monkey) {
+        if ignore_pred(monkey) {
             continue;
         }
         if let Some(number) =
-            monkey_map[monkey].resolve_with(|monkey| concrete_numbers.get(&monkey).copied())
+            monkey_map[&monkey].resolve_with(|monkey| concrete_numbers.get(&monkey).copied())
         {
-            concrete_numbers.insert(*monkey, number);
+            concrete_numbers.insert(monkey, number);
         }
     }
     concrete_numbers
@@ -123,26 +144,54 @@ fn get_concrete_numbers_ignoring(
 const ROOT: MonkeyName = MonkeyName(['r', 'o', 'o', 't']);
 const HUMN: MonkeyName = MonkeyName(['h', 'u', 'm', 'n']);
 
-pub fn part_1(input: &[RowData]) -> isize {
+fn monkey_map(input: &[RowData]) -> Result<HashMap<MonkeyName, MonkeyYell>, SolveError> {
     let monkey_map: HashMap<MonkeyName, MonkeyYell> = input.iter().cloned().collect();
-    get_concrete_numbers_ignoring(&monkey_map, ROOT, |_| false)[&ROOT]
+    if !monkey_map.contains_key(&ROOT) {
+        return Err(SolveError::MissingStart("monkey root".to_owned()));
+    }
+    Ok(monkey_map)
 }
 
-pub fn part_2(input: &[RowData]) -> isize {
-    let monkey_map: HashMap<MonkeyName, MonkeyYell> = input.iter().cloned().collect();
+fn unsolvable(reason: &str) -> SolveError {
+    SolveError::Unsolvable(reason.to_owned())
+}
+
+pub fn part_1(input: &[RowData]) -> Result<isize, SolveError> {
+    let monkey_map = monkey_map(input)?;
+    get_concrete_numbers_ignoring(&monkey_map, ROOT, |_| false)
+        .get(&ROOT)
+        .copied()
+        .ok_or_else(|| unsolvable("root never gets a number"))
+}
+
+pub fn part_2(input: &[RowData]) -> Result<isize, SolveError> {
+    let monkey_map = monkey_map(input)?;
+    if !monkey_map.contains_key(&HUMN) {
+        return Err(SolveError::MissingStart("monkey humn".to_owned()));
+    }
     let concrete_numbers =
         get_concrete_numbers_ignoring(&monkey_map, ROOT, |monkey_name| monkey_name == HUMN);
-    assert!(!concrete_numbers.contains_key(&ROOT));
-    let root_deps = monkey_map[&ROOT].dependencies().unwrap();
+    let root_deps = monkey_map[&ROOT]
+        .dependencies()
+        .ok_or_else(|| unsolvable("root yells a number instead of comparing two monkeys"))?;
     let (mut monkey_needs_to_be, mut needs_to_be) =
         match root_deps.map(|monkey_name| concrete_numbers.get(&monkey_name)) {
             [Some(num), None] => (root_deps[1], *num),
             [None, Some(num)] => (root_deps[0], *num),
-            _ => panic!(),
+            _ => return Err(unsolvable("humn is not on exactly one side of root")),
         };
-    assert!(!concrete_numbers.contains_key(&monkey_needs_to_be));
+    let divide = |dividend: isize, divisor: isize| {
+        dividend
+            .checked_div(divisor)
+            .ok_or_else(|| unsolvable("humn would have to divide by zero"))
+    };
 
+    let mut visited = HashSet::new();
     while monkey_needs_to_be != HUMN {
+        if !visited.insert(monkey_needs_to_be) {
+            return Err(unsolvable("the monkeys wait for each other in a loop"));
+        }
+        // Only humn and the monkeys waiting for it have no number, and humn was checked above.
         let deps = monkey_map[&monkey_needs_to_be]
             .dependencies()
             .unwrap()
@@ -167,11 +216,11 @@ pub fn part_2(input: &[RowData]) -> isize {
             }
 
             (MonkeyYell::Mul(monkey, _), [None, Some(number)]) => {
-                needs_to_be /= number;
+                needs_to_be = divide(needs_to_be, number)?;
                 monkey_needs_to_be = *monkey;
             }
             (MonkeyYell::Mul(_, monkey), [Some(number), None]) => {
-                needs_to_be /= number;
+                needs_to_be = divide(needs_to_be, number)?;
                 monkey_needs_to_be = *monkey;
             }
 
@@ -180,11 +229,148 @@ pub fn part_2(input: &[RowData]) -> isize {
                 monkey_needs_to_be = *monkey;
             }
             (MonkeyYell::Div(_, monkey), [Some(number), None]) => {
-                needs_to_be = number / needs_to_be;
+                needs_to_be = divide(number, needs_to_be)?;
                 monkey_needs_to_be = *monkey;
             }
-            _ => panic!(),
+            _ => return Err(unsolvable("humn is on both sides of a monkey")),
+        }
+    }
+    Ok(needs_to_be)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+root: pppw + sjmn
+dbpl: 5
+cczh: sllz + lgvd
+zczc: 2
+ptdq: humn - dvpt
+dvpt: 3
+lfqf: 4
+humn: 5
+ljgn: 2
+sjmn: drzm * dbpl
+sllz: 4
+pppw: cczh / lfqf
+lgvd: ljgn * ptdq
+drzm: hmdt - zczc
+hmdt: 32
+";
+
+    #[test]
+    fn part_1_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part_1(&input), Ok(152));
+    }
+
+    #[test]
+    fn part_2_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part_2(&input), Ok(301));
+    }
+
+    #[test]
+    fn unknown_monkey() {
+        let err = generator("root: abcd + efgh\nabcd: 5\n").unwrap_err();
+        assert_eq!((err.line, err.column, err.text.as_str()), (1, 14, "efgh"));
+        assert_eq!(err.expected, "a known monkey");
+    }
+
+    #[test]
+    fn unsolvable_inputs() {
+        let solve = |text: &str| {
+            let input = generator(text).unwrap();
+            (part_1(&input), part_2(&input))
+        };
+        let missing = |what: &str| Err(SolveError::MissingStart(what.to_owned()));
+        let unsolvable = |reason: &str| Err(unsolvable(reason));
+
+        assert_eq!(
+            solve("abcd: 5\n"),
+            (missing("monkey root"), missing("monkey root"))
+        );
+        assert_eq!(
+            solve("root: abcd + efgh\nabcd: 5\nefgh: 2\n"),
+            (Ok(7), missing("monkey humn"))
+        );
+        assert_eq!(
+            solve("root: 3\nhumn: 5\n"),
+            (
+                Ok(3),
+                unsolvable("root yells a number instead of comparing two monkeys")
+            )
+        );
+        assert_eq!(
+            solve("root: humn + humn\nhumn: 5\n").1,
+            unsolvable("humn is not on exactly one side of root")
+        );
+        assert_eq!(
+            solve("root: abcd + efgh\nabcd: humn * humn\nhumn: 5\nefgh: 4\n").1,
+            unsolvable("humn is on both sides of a monkey")
+        );
+        assert_eq!(
+            solve("root: abcd + efgh\nabcd: humn * zero\nzero: 0\nhumn: 5\nefgh: 4\n").1,
+            unsolvable("humn would have to divide by zero")
+        );
+        assert_eq!(
+            solve("root: abcd + efgh\nabcd: efgh / zero\nzero: 0\nefgh: 4\n").0,
+            unsolvable("root never gets a number")
+        );
+        assert_eq!(
+            solve("root: abcd + efgh\nabcd: bcde + efgh\nbcde: abcd + efgh\nhumn: 5\nefgh: 4\n"),
+            (
+                unsolvable("root never gets a number"),
+                unsolvable("the monkeys wait for each other in a loop")
+            )
+        );
+    }
+
+    /// Evaluates the whole tree, with `humn` yelling `human` instead of its own number.
+    fn reference_yell(
+        monkey_map: &HashMap<MonkeyName, MonkeyYell>,
+        monkey: MonkeyName,
+        human: Option<isize>,
+    ) -> isize {
+        if let (HUMN, Some(human)) = (monkey, human) {
+            return human;
+        }
+        monkey_map[&monkey]
+            .resolve_with(|dep| Some(reference_yell(monkey_map, dep, human)))
+            .unwrap()
+    }
+
+    /// Tries every number for `humn`, starting from zero.
+    fn reference_part_2(monkey_map: &HashMap<MonkeyName, MonkeyYell>) -> isize {
+        let [left, right] = monkey_map[&ROOT].dependencies().unwrap();
+        (0..)
+            .find(|&human| {
+                reference_yell(monkey_map, left, Some(human))
+                    == reference_yell(monkey_map, right, Some(human))
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn matches_reference_on_random_inputs() {
+        for seed in 0..20 {
+            let text = crate::random_input::generate(21, seed, 1 + seed as usize).unwrap();
+            let input = generator(&text).unwrap();
+            let monkey_map: HashMap<MonkeyName, MonkeyYell> = input.iter().cloned().collect();
+            assert_eq!(
+                part_1(&input),
+                Ok(reference_yell(&monkey_map, ROOT, None)),
+                "{}",
+                text
+            );
+            assert_eq!(
+                part_2(&input),
+                Ok(reference_part_2(&monkey_map)),
+                "{}",
+                text
+            );
         }
     }
-    needs_to_be
 }
@@ -4,6 +4,11 @@ use std::ops::{Index, Range};
 use itertools::Itertools;
 use num::integer::Roots;
 
+use crate::error::ParseError;
+use crate::parsing::{chars, integer, Pattern};
+use crate::solution::Part;
+use crate::visualize::{Frame, Rgb, Visualize};
+
 #[derive(Debug)]
 pub struct BoardMap {
     rows: Vec<(Range<usize>, Vec<bool>)>,
@@ -17,25 +22,29 @@ pub enum Instruction {
     Left,
 }
 
-pub fn generator(input: &str) -> (BoardMap, Vec<Instruction>) {
+pub fn generator(input: &str) -> Result<(BoardMap, Vec<Instruction>), ParseError> {
     let mut it = input.lines();
     let rows = it
         .by_ref()
         .take_while(|l| !l.is_empty())
         .map(|line| {
-            let mut it = line.chars().peekable();
-            let offset = it.peeking_take_while(|c| *c == ' ').count();
-            (
-                offset..line.len(),
-                it.map(|c| match c {
-                    '.' => false,
-                    '#' => true,
-                    _ => panic!(),
+            let offset = line.len() - line.trim_start_matches(' ').len();
+            let tiles = chars(&line[offset..])
+                .map(|(c, at)| match c {
+                    '.' => Ok(false),
+                    '#' => Ok(true),
+                    _ => Err(ParseError::new(input, at, "'.' or '#'")),
                 })
-                .collect_vec(),
-            )
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok((offset..line.len(), tiles))
         })
-        .collect_vec();
+        .collect::<Result<Vec<_>, _>>()?;
+    if !rows.first().is_some_and(|(_, row)| row.contains(&false)) {
+        return Err(ParseError::at_end(
+            input,
+            "a board map with an open tile on its first row",
+        ));
+    }
     #[allow(clippy::reversed_empty_ranges)]
     let mut col_ranges =
         vec![usize::MAX..0; rows.iter().map(|(range, _)| range.end).max().unwrap()];
@@ -47,17 +56,23 @@ pub fn generator(input: &str) -> (BoardMap, Vec<Instruction>) {
         }
     }
     let board_map = BoardMap { rows, col_ranges };
-    let instructions_text = it.next().unwrap();
-    let pattern = regex::Regex::new(r#"\d+|[RL]"#).unwrap();
-    let instructions = pattern
+    let instructions_text = it
+        .next()
+        .ok_or_else(|| ParseError::at_end(input, "the path description"))?;
+    static INSTRUCTION: Pattern = Pattern::new(r"\d+|[RL]|.");
+    let instructions = INSTRUCTION
+        .regex()
         .find_iter(instructions_text)
         .map(|m| match m.as_str() {
-            "R" => Instruction::Right,
-            "L" => Instruction::Left,
-            num => Instruction::Walk(num.parse().unwrap()),
+            "R" => Ok(Instruction::Right),
+            "L" => Ok(Instruction::Left),
+            num => Ok(Instruction::Walk(integer(input, num, "a number, R or L")?)),
         })
-        .collect_vec();
-    (board_map, instructions)
+        .collect::<Result<Vec<_>, _>>()?;
+    if let Some(extra) = it.find(|line| !line.is_empty()) {
+        return Err(ParseError::new(input, extra, "end of input"));
+    }
+    Ok((board_map, instructions))
 }
 
 impl Index<[usize; 2]> for BoardMap {
@@ -168,6 +183,21 @@ impl Display for State<'_> {
     }
 }
 
+impl Visualize for State<'_> {
+    fn frame(&self) -> Frame {
+        let mut frame = Frame::new(self.map.col_ranges.len(), self.map.rows.len(), Rgb::BLACK);
+        for (r, (range, row)) in self.map.rows.iter().enumerate() {
+            for (i, wall) in row.iter().enumerate() {
+                let color = if *wall { Rgb::DARK_GRAY } else { Rgb::SAND };
+                frame.set([range.start + i, r], color);
+            }
+        }
+        let [r, c] = self.pos;
+        frame.set([c, r], Rgb::RED);
+        frame
+    }
+}
+
 impl<'a> State<'a> {
     fn new(map: &'a BoardMap) -> Self {
         let (range, row) = &map.rows[0];
@@ -183,6 +213,7 @@ impl<'a> State<'a> {
         &mut self,
         instruction: &Instruction,
         mut wrap_dlg: impl FnMut([usize; 2], Direction) -> ([usize; 2], Direction),
+        observe: &mut dyn FnMut(&dyn Visualize),
     ) {
         match instruction {
             Instruction::Walk(num_steps) => {
@@ -202,6 +233,7 @@ impl<'a> State<'a> {
                     }
                     self.pos = new_pos;
                     self.direction = new_direction;
+                    observe(self);
                 }
             }
             Instruction::Right => {
@@ -218,19 +250,31 @@ impl<'a> State<'a> {
     }
 }
 
-pub fn part_1((board_map, instructions): &(BoardMap, Vec<Instruction>)) -> usize {
+fn walk_flat(
+    (board_map, instructions): &(BoardMap, Vec<Instruction>),
+    observe: &mut dyn FnMut(&dyn Visualize),
+) -> usize {
     let mut state = State::new(board_map);
+    observe(&state);
     for instruction in instructions.iter() {
-        state.apply_instruction(instruction, |[r, c], direction| match direction {
-            Direction::Right => ([r, state.map.rows[r].0.start], direction),
-            Direction::Down => ([state.map.col_ranges[c].start, c], direction),
-            Direction::Left => ([r, state.map.rows[r].0.end - 1], direction),
-            Direction::Up => ([state.map.col_ranges[c].end - 1, c], direction),
-        });
+        state.apply_instruction(
+            instruction,
+            |[r, c], direction| match direction {
+                Direction::Right => ([r, board_map.rows[r].0.start], direction),
+                Direction::Down => ([board_map.col_ranges[c].start, c], direction),
+                Direction::Left => ([r, board_map.rows[r].0.end - 1], direction),
+                Direction::Up => ([board_map.col_ranges[c].end - 1, c], direction),
+            },
+            observe,
+        );
     }
     state.calc_password()
 }
 
+pub fn part_1(input: &(BoardMap, Vec<Instruction>)) -> usize {
+    walk_flat(input, &mut |_| {})
+}
+
 const CUBE_SIDE_LINKS: [[usize; 4]; 6] = [
     //              >  V  <  ^
     /*0 - bottom*/ [1, 2, 3, 4],
@@ -241,7 +285,10 @@ const CUBE_SIDE_LINKS: [[usize; 4]; 6] = [
     /*5 -    top*/ [1, 4, 3, 2],
 ];
 
-pub fn part_2((board_map, instructions): &(BoardMap, Vec<Instruction>)) -> usize {
+fn walk_cube(
+    (board_map, instructions): &(BoardMap, Vec<Instruction>),
+    observe: &mut dyn FnMut(&dyn Visualize),
+) -> usize {
     let face_side = (board_map
         .rows
         .iter()
@@ -303,8 +350,9 @@ pub fn part_2((board_map, instructions): &(BoardMap, Vec<Instruction>)) -> usize
         .map(|(_, pos, orientation)| (pos, orientation))
         .collect_vec();
     let mut state = State::new(board_map);
+    observe(&state);
     for instruction in instructions.iter() {
-        state.apply_instruction(instruction, |pos, direction| {
+        let wrap = |pos: [usize; 2], direction: Direction| {
             let [fr, fc] = pos.map(|n| n / face_side);
             let (current_face_idx, current_face_orientation) = faces_by_position[fr][fc].unwrap();
             let new_face_idx =
@@ -336,7 +384,57 @@ pub fn part_2((board_map, instructions): &(BoardMap, Vec<Instruction>)) -> usize
             let new_pos = [nr * face_side + nor, nc * face_side + noc];
 
             (new_pos, new_direction)
-        });
+        };
+        state.apply_instruction(instruction, wrap, observe);
     }
     state.calc_password()
 }
+
+pub fn part_2(input: &(BoardMap, Vec<Instruction>)) -> usize {
+    walk_cube(input, &mut |_| {})
+}
+
+/// Shows the position after each step of the walk.
+pub fn visualize(
+    input: &(BoardMap, Vec<Instruction>),
+    part: Part,
+    observe: &mut dyn FnMut(&dyn Visualize),
+) {
+    match part {
+        Part::One => walk_flat(input, observe),
+        Part::Two => walk_cube(input, observe),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "        ...#
+        .#..
+        #...
+        ....
+...#.......#
+........#...
+..#....#....
+..........#.
+        ...#....
+        .....#..
+        .#......
+        ......#.
+
+10R5L5R10L4R5L5
+";
+
+    #[test]
+    fn part_1_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part_1(&input), 6032);
+    }
+
+    #[test]
+    fn part_2_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part_2(&input), 5031);
+    }
+}
@@ -4,20 +4,31 @@ use std::ops::RangeInclusive;
 use hashbrown::{HashMap, HashSet};
 use itertools::Itertools;
 
+use crate::error::ParseError;
+use crate::parsing::chars;
+use crate::solution::Part;
+use crate::visualize::{Frame, Rgb, Visualize};
+
 type Coord = [isize; 2];
 
-pub fn generator(input: &str) -> Vec<Coord> {
-    input
+pub fn generator(input: &str) -> Result<Vec<Coord>, ParseError> {
+    let elves = input
         .lines()
         .enumerate()
         .flat_map(|(y, line)| {
-            line.chars().enumerate().filter_map(move |(x, c)| match c {
-                '.' => None,
-                '#' => Some([x as isize, y as isize]),
-                _ => panic!(),
-            })
+            chars(line)
+                .enumerate()
+                .filter_map(move |(x, (c, at))| match c {
+                    '.' => None,
+                    '#' => Some(Ok([x as isize, y as isize])),
+                    _ => Some(Err(ParseError::new(input, at, "'.' or '#'"))),
+                })
         })
-        .collect()
+        .collect::<Result<Vec<_>, _>>()?;
+    if elves.is_empty() {
+        return Err(ParseError::at_end(input, "at least one elf"));
+    }
+    Ok(elves)
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -122,6 +133,22 @@ impl Display for State {
     }
 }
 
+impl Visualize for State {
+    fn frame(&self) -> Frame {
+        let [x_limits, y_limits] = self.limits();
+        let mut frame = Frame::new(
+            (x_limits.end() - x_limits.start() + 1) as usize,
+            (y_limits.end() - y_limits.start() + 1) as usize,
+            Rgb::BLACK,
+        );
+        for [x, y] in self.elves.iter() {
+            let cell = [x - x_limits.start(), y - y_limits.start()];
+            frame.set(cell.map(|n| n as usize), Rgb::GREEN);
+        }
+        frame
+    }
+}
+
 impl State {
     fn limits(&self) -> [RangeInclusive<isize>; 2] {
         let limits = [
@@ -192,26 +219,95 @@ impl State {
     }
 }
 
-pub fn part_1(input: &[Coord]) -> usize {
+/// Returns the final state and the number of rounds, the last of them the first where no elf
+/// moved if that happened within `max_rounds`.
+fn spread(
+    input: &[Coord],
+    max_rounds: usize,
+    observe: &mut dyn FnMut(&dyn Visualize),
+) -> (State, usize) {
     let mut state = State {
         elves: input.iter().copied().collect(),
     };
-
-    for i in 0..10 {
-        state.step(i);
+    observe(&state);
+    for i in 0..max_rounds {
+        let any_moved = state.step(i);
+        observe(&state);
+        if !any_moved {
+            return (state, i + 1);
+        }
     }
+    (state, max_rounds)
+}
+
+pub fn part_1(input: &[Coord]) -> usize {
+    // Once no elf moves, more rounds change nothing.
+    let (state, _) = spread(input, 10, &mut |_| {});
     state.calc_empty_ground()
 }
 
 pub fn part_2(input: &[Coord]) -> usize {
-    let mut state = State {
-        elves: input.iter().copied().collect(),
+    let (_, rounds) = spread(input, usize::MAX, &mut |_| {});
+    rounds
+}
+
+/// Shows the elves after each round. Part 1 stops after ten rounds, part 2 once they stop moving.
+pub fn visualize(input: &[Coord], part: Part, observe: &mut dyn FnMut(&dyn Visualize)) {
+    let max_rounds = match part {
+        Part::One => 10,
+        Part::Two => usize::MAX,
     };
-    for i in 0.. {
-        let any_moved = state.step(i);
-        if !any_moved {
-            return i + 1;
+    spread(input, max_rounds, observe);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+....#..
+..###.#
+#...#.#
+.#...##
+#.###..
+##.#.##
+.#..#..
+";
+
+    #[test]
+    fn part_1_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part_1(&input), 110);
+    }
+
+    #[test]
+    fn part_2_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part_2(&input), 20);
+    }
+
+    #[test]
+    fn state_display() {
+        let mut state = State {
+            elves: generator(EXAMPLE).unwrap().into_iter().collect(),
+        };
+        for i in 0..10 {
+            state.step(i);
         }
+        assert_eq!(
+            state.to_string(),
+            "\n\
+             ......#.....\n\
+             ..........#.\n\
+             .#.#..#.....\n\
+             .....#......\n\
+             ..#.....#..#\n\
+             #......##...\n\
+             ....##......\n\
+             .#........#.\n\
+             ...#.#..#...\n\
+             ............\n\
+             ...#..#..#.."
+        );
     }
-    unreachable!()
 }
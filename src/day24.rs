@@ -5,7 +5,11 @@ use std::ops::Index;
 use enumflags2::{bitflags, make_bitflags, BitFlags};
 use itertools::Itertools;
 
-use crate::bfs::HashMapBfs;
+use crate::bfs::HashMapAStar;
+use crate::error::{ParseError, SolveError};
+use crate::parsing::chars;
+use crate::solution::Part;
+use crate::visualize::{Frame, Rgb, Visualize};
 
 type Coord = [isize; 2];
 
@@ -29,50 +33,78 @@ pub struct WindState {
     cells: Vec<WindFlags>,
 }
 
-pub fn generator(input: &str) -> WindState {
-    let mut it = input.lines();
-    let first_line = it.next().unwrap();
-    for (i, c) in first_line.chars().enumerate() {
-        if i == 1 {
-            assert!(c == '.');
+fn check_wall_line(input: &str, line: &str, opening_at: usize) -> Result<(), ParseError> {
+    for (i, (c, at)) in chars(line).enumerate() {
+        let (required, expected) = if i == opening_at {
+            ('.', "the valley opening '.'")
         } else {
-            assert!(c == '#');
+            ('#', "a wall '#'")
+        };
+        if c != required {
+            return Err(ParseError::new(input, at, expected));
         }
     }
-    assert!(&first_line[..2] == "#.");
-    assert!(&first_line.starts_with("#."));
-    assert!(&first_line.chars().skip(2).all(|c| c == '#'));
-    let height = input.lines().count() - 2;
+    Ok(())
+}
+
+pub fn generator(input: &str) -> Result<WindState, ParseError> {
+    let lines = input.lines().collect_vec();
+    let [first_line, middle_lines @ .., last_line] = lines.as_slice() else {
+        return Err(ParseError::at_end(
+            input,
+            "a valley of at least three lines",
+        ));
+    };
+    if first_line.len() < 3 || middle_lines.is_empty() {
+        return Err(ParseError::new(
+            input,
+            first_line,
+            "a valley with room inside",
+        ));
+    }
+    check_wall_line(input, first_line, 1)?;
+    let height = middle_lines.len();
     let width = first_line.len() - 2;
-    let cells = it
-        .by_ref()
-        .take(height)
-        .flat_map(|line| {
-            assert!(line.len() == width + 2);
-            assert!(line.starts_with('#'));
-            assert!(line.ends_with('#'));
-            line.chars().skip(1).take(width).map(|c| match c {
+    let mut cells = Vec::with_capacity(width * height);
+    for line in middle_lines {
+        if line.len() != width + 2 {
+            return Err(ParseError::new(
+                input,
+                line,
+                format!("a line {} characters long", width + 2),
+            ));
+        }
+        for (i, (c, at)) in chars(line).enumerate() {
+            if i == 0 || i == width + 1 {
+                if c != '#' {
+                    return Err(ParseError::new(input, at, "a wall '#'"));
+                }
+                continue;
+            }
+            let cell = match c {
                 '.' => WindFlags::EMPTY,
                 '^' => WindFlags::from_flag(Wind::Up),
                 'v' => WindFlags::from_flag(Wind::Down),
                 '<' => WindFlags::from_flag(Wind::Left),
                 '>' => WindFlags::from_flag(Wind::Right),
-                _ => panic!(),
-            })
-        })
-        .collect();
-    for (i, c) in it.next().unwrap().chars().enumerate() {
-        if i == width {
-            assert!(c == '.');
-        } else {
-            assert!(c == '#');
+                _ => return Err(ParseError::new(input, at, "'.', '^', 'v', '<' or '>'")),
+            };
+            cells.push(cell);
         }
     }
-    WindState {
+    if last_line.len() != width + 2 {
+        return Err(ParseError::new(
+            input,
+            last_line,
+            format!("a line {} characters long", width + 2),
+        ));
+    }
+    check_wall_line(input, last_line, width)?;
+    Ok(WindState {
         width,
         height,
         cells,
-    }
+    })
 }
 
 impl Display for Wind {
@@ -202,8 +234,7 @@ impl WindState {
         }
     }
 
-    #[allow(unused)]
-    fn with_elf_at(&self, pos: Coord) -> WindStateWithElf {
+    fn with_elf_at(&self, pos: Coord) -> WindStateWithElf<'_> {
         WindStateWithElf(self, pos)
     }
 
@@ -224,58 +255,152 @@ impl Display for WindStateWithElf<'_> {
     }
 }
 
+impl Visualize for WindStateWithElf<'_> {
+    fn frame(&self) -> Frame {
+        let WindStateWithElf(wind_state, pos) = self;
+        let mut frame = Frame::new(wind_state.width + 2, wind_state.height + 2, Rgb::BLACK);
+        for y in -1..(wind_state.height as isize + 1) {
+            for x in -1..(wind_state.width as isize + 1) {
+                let winds = wind_state[[x, y]];
+                let color = if winds.contains(Wind::Wall) {
+                    Rgb::GRAY
+                } else if winds.is_empty() {
+                    continue;
+                } else {
+                    Rgb::BLUE.mix(Rgb::WHITE, (winds.len() - 1) as f64 / 3.0)
+                };
+                frame.set([x + 1, y + 1].map(|n| n as usize), color);
+            }
+        }
+        frame.set(pos.map(|n| (n + 1) as usize), Rgb::RED);
+        frame
+    }
+}
+
 fn possible_movements([x, y]: Coord) -> impl Iterator<Item = Coord> {
     [[-1, 0], [1, 0], [0, -1], [0, 1], [0, 0]]
         .into_iter()
         .map(move |[dx, dy]| [x + dx, y + dy])
 }
 
+/// The positions along a shortest trip, each with the index of the wind state at that time.
 fn calc_trip(
     all_wind_states: &[WindState],
     start_state_idx: usize,
     start: Coord,
     end: Coord,
-) -> usize {
-    let mut bfs = HashMapBfs::<(Coord, usize), usize>::new();
-    bfs.add_root((start, start_state_idx), 0);
-    while let Some((pos, wind_idx)) = bfs.consider_next() {
-        if pos == end {
-            return *bfs.cost(&(pos, wind_idx)).unwrap();
-        }
+) -> Result<Vec<(Coord, usize)>, SolveError> {
+    let astar =
+        HashMapAStar::new(|([x, y], _): &(Coord, usize)| x.abs_diff(end[0]) + y.abs_diff(end[1]));
+    let mut walk = astar.walk([((start, start_state_idx), 0)], |&(pos, wind_idx)| {
         let next_wind_idx = (wind_idx + 1) % all_wind_states.len();
         let next_wind_state = &all_wind_states[next_wind_idx];
-        for next_pos in possible_movements(pos) {
-            if next_wind_state[next_pos].is_empty() {
-                bfs.add_edge((pos, wind_idx), (next_pos, next_wind_idx), 1);
-            }
-        }
-    }
-    0
+        possible_movements(pos)
+            .filter(|&next_pos| next_wind_state[next_pos].is_empty())
+            .map(move |next_pos| ((next_pos, next_wind_idx), 1))
+    });
+    let arrival = walk
+        .find_first(|&(pos, _)| pos == end)
+        .ok_or(SolveError::Unreachable)?;
+    Ok(walk.search().path_to(&arrival))
 }
 
-pub fn part_1(input: &WindState) -> usize {
+fn travel(
+    input: &WindState,
+    trips: &[(Coord, Coord)],
+    observe: &mut dyn FnMut(&dyn Visualize),
+) -> Result<usize, SolveError> {
     let all_wind_states = successors(Some(input.clone()), |wind_state| Some(wind_state.advance()))
         .take(input.period())
         .collect_vec();
-    calc_trip(
-        &all_wind_states,
-        0,
-        input.start_position(),
-        input.end_position(),
-    )
+    let mut total = 0;
+    for (from, to) in trips {
+        let path = calc_trip(&all_wind_states, total % all_wind_states.len(), *from, *to)?;
+        for (pos, wind_idx) in path.iter() {
+            observe(&all_wind_states[*wind_idx].with_elf_at(*pos));
+        }
+        total += path.len() - 1;
+    }
+    Ok(total)
 }
 
-pub fn part_2(input: &WindState) -> usize {
-    let all_wind_states = successors(Some(input.clone()), |wind_state| Some(wind_state.advance()))
-        .take(input.period())
-        .collect_vec();
-    let mut total = 0;
-    for (from, to) in [
-        (input.start_position(), input.end_position()),
-        (input.end_position(), input.start_position()),
-        (input.start_position(), input.end_position()),
-    ] {
-        total += calc_trip(&all_wind_states, total, from, to);
+fn trips(input: &WindState, part: Part) -> Vec<(Coord, Coord)> {
+    let there = (input.start_position(), input.end_position());
+    let back = (input.end_position(), input.start_position());
+    match part {
+        Part::One => vec![there],
+        Part::Two => vec![there, back, there],
+    }
+}
+
+pub fn part_1(input: &WindState) -> Result<usize, SolveError> {
+    travel(input, &trips(input, Part::One), &mut |_| {})
+}
+
+pub fn part_2(input: &WindState) -> Result<usize, SolveError> {
+    travel(input, &trips(input, Part::Two), &mut |_| {})
+}
+
+/// Shows the elf following the shortest path through the blizzards.
+pub fn visualize(input: &WindState, part: Part, observe: &mut dyn FnMut(&dyn Visualize)) {
+    // Without a way through, there is no elf to follow.
+    let _ = travel(input, &trips(input, part), observe);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+#.######
+#>>.<^<#
+#.<..<<#
+#>v.><>#
+#<^v^^>#
+######.#
+";
+
+    #[test]
+    fn part_1_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part_1(&input), Ok(18));
+    }
+
+    #[test]
+    fn part_2_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part_2(&input), Ok(54));
+    }
+
+    #[test]
+    fn blocked_valley() {
+        // The two blizzards take turns filling both squares.
+        let input = generator("#.#\n#v#\n#^#\n#.#\n").unwrap();
+        assert_eq!(part_1(&input), Err(SolveError::Unreachable));
+    }
+
+    #[test]
+    fn wind_in_the_side_wall() {
+        let err = generator("#.###\n#..>.\n#...#\n###.#\n").unwrap_err();
+        assert_eq!(
+            (err.line, err.column, err.expected.as_str()),
+            (2, 5, "a wall '#'")
+        );
+    }
+
+    #[test]
+    fn wind_state_display() {
+        let wind_state = generator(EXAMPLE).unwrap();
+        assert_eq!(wind_state.to_string(), format!("\n{}", EXAMPLE.trim_end()));
+        assert_eq!(
+            wind_state.advance().with_elf_at([0, 0]).to_string(),
+            "\n\
+             #.######\n\
+             #E>3.<.#\n\
+             #<..<<.#\n\
+             #>2.22.#\n\
+             #>v..^<#\n\
+             ######.#"
+        );
     }
-    total
 }
@@ -1,5 +1,19 @@
-pub fn generator(input: &str) -> Vec<String> {
-    input.lines().map(|line| line.to_owned()).collect()
+use crate::error::ParseError;
+use crate::parsing::check_chars;
+
+pub fn generator(input: &str) -> Result<Vec<String>, ParseError> {
+    input
+        .lines()
+        .map(|line| {
+            check_chars(
+                input,
+                line,
+                |c| matches!(c, '0' | '1' | '2' | '-' | '='),
+                "a snafu digit",
+            )?;
+            Ok(line.to_owned())
+        })
+        .collect()
 }
 
 fn parse_snafu(snafu: &str) -> isize {
@@ -50,3 +64,47 @@ pub fn part_2(input: &[String]) -> isize {
     let _ = input;
     0
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+1=-0-2
+12111
+2=0=
+21
+2=01
+111
+20012
+112
+1=-1=
+1-12
+12
+1=
+122
+";
+
+    #[test]
+    fn snafu_conversions() {
+        for (decimal, snafu) in [
+            (1, "1"),
+            (2, "2"),
+            (3, "1="),
+            (4, "1-"),
+            (5, "10"),
+            (2022, "1=11-2"),
+            (12345, "1-0---0"),
+            (314159265, "1121-1110-1=0"),
+        ] {
+            assert_eq!(parse_snafu(snafu), decimal);
+            assert_eq!(format_snafu(decimal), snafu);
+        }
+    }
+
+    #[test]
+    fn part_1_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part_1(&input), "2=-1=0");
+    }
+}
@@ -1,13 +1,27 @@
 use hashbrown::HashSet;
 use itertools::Itertools;
 
+use crate::error::ParseError;
+use crate::parsing::check_chars;
+
 #[derive(Debug, Clone)]
 pub struct Rucksack(Vec<char>);
 
-pub fn generator(input: &str) -> Vec<Rucksack> {
+pub fn generator(input: &str) -> Result<Vec<Rucksack>, ParseError> {
     input
         .lines()
-        .map(|line| Rucksack(line.chars().collect()))
+        .map(|line| {
+            check_chars(
+                input,
+                line,
+                |c| c.is_ascii_alphabetic(),
+                "an item type letter",
+            )?;
+            if line.len() % 2 != 0 {
+                return Err(ParseError::new(input, line, "an even number of items"));
+            }
+            Ok(Rucksack(line.chars().collect()))
+        })
         .collect()
 }
 
@@ -74,3 +88,29 @@ pub fn part_2(input: &[Rucksack]) -> usize {
         .map(|elves_group| item_type_priority(elves_group.badge()))
         .sum()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+vJrwpWtwJgWrhcsFMMfFFhFp
+jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL
+PmmdzqPrVvPwwTWBwg
+wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn
+ttgJtRGJQctTZtZT
+CrZsJsPPZsGzwwsLwLmpwMDw
+";
+
+    #[test]
+    fn part_1_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part_1(&input), 157);
+    }
+
+    #[test]
+    fn part_2_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part_2(&input), 70);
+    }
+}
@@ -1,17 +1,21 @@
 use std::ops::RangeInclusive;
 
+use crate::error::ParseError;
+use crate::parsing::{integer, pair};
+
 #[derive(Debug)]
 pub struct PairRanges([RangeInclusive<usize>; 2]);
 
-pub fn generator(input: &str) -> Vec<PairRanges> {
+pub fn generator(input: &str) -> Result<Vec<PairRanges>, ParseError> {
+    let parse_range = |elf: &str| -> Result<RangeInclusive<usize>, ParseError> {
+        let (start, end) = pair(input, elf, "-", "a range like 2-4")?;
+        Ok(integer(input, start, "a section number")?..=integer(input, end, "a section number")?)
+    };
     input
         .lines()
         .map(|line| {
-            let mut it = line.split(',').map(|elf| {
-                let mut it = elf.split('-');
-                it.next().unwrap().parse().unwrap()..=it.next().unwrap().parse().unwrap()
-            });
-            PairRanges([it.next().unwrap(), it.next().unwrap()])
+            let (first, second) = pair(input, line, ",", "two comma separated ranges")?;
+            Ok(PairRanges([parse_range(first)?, parse_range(second)?]))
         })
         .collect()
 }
@@ -46,3 +50,29 @@ pub fn part_2(input: &[PairRanges]) -> usize {
         .filter(|pair_ranges| pair_ranges.is_overlapping())
         .count()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+2-4,6-8
+2-3,4-5
+5-7,7-9
+2-8,3-7
+6-6,4-6
+2-6,4-8
+";
+
+    #[test]
+    fn part_1_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part_1(&input), 2);
+    }
+
+    #[test]
+    fn part_2_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part_2(&input), 4);
+    }
+}
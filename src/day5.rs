@@ -1,6 +1,7 @@
 use std::fmt::Display;
 
-use itertools::Itertools;
+use crate::error::ParseError;
+use crate::parsing::{integer, Pattern};
 
 type Input = (Arrangement, Vec<Command>);
 
@@ -14,25 +15,34 @@ pub struct Command {
     to: usize,
 }
 
-pub fn generator(input: &str) -> Input {
-    let lines = input.lines().collect_vec();
-    let (sepline, _) = lines.iter().find_position(|line| line.is_empty()).unwrap();
-    let (arrangement, commands) = lines.split_at(sepline);
+pub fn generator(input: &str) -> Result<Input, ParseError> {
+    static COMMAND: Pattern = Pattern::new(r"^move (\d+) from (\d+) to (\d+)$");
+    let (arrangement, commands) = input.split_once("\n\n").ok_or_else(|| {
+        ParseError::at_end(input, "an empty line between the stacks and the commands")
+    })?;
     let arrangement = Arrangement({
-        let mut it = arrangement.iter().rev();
-        let stacks_index = it
+        let mut it = arrangement.lines().rev();
+        let index_line = it
             .next()
-            .unwrap()
-            .chars()
-            .map(|c| {
+            .ok_or_else(|| ParseError::new(input, arrangement, "the stacks numbers line"))?;
+        let stacks_index = index_line
+            .char_indices()
+            .map(|(i, c)| {
                 if c == ' ' {
-                    None
+                    Ok(None)
                 } else {
-                    Some(c as usize - '0' as usize - 1)
+                    match c.to_digit(10) {
+                        Some(digit) if 0 < digit => Ok(Some(digit as usize - 1)),
+                        _ => Err(ParseError::new(
+                            input,
+                            &index_line[i..i + c.len_utf8()],
+                            "a stack number",
+                        )),
+                    }
                 }
             })
-            .collect_vec();
-        let num_stacks = stacks_index.iter().filter(|idx| idx.is_some()).count();
+            .collect::<Result<Vec<_>, _>>()?;
+        let num_stacks = stacks_index.iter().flatten().max().map_or(0, |idx| idx + 1);
         let mut stacks = vec![Vec::<char>::new(); num_stacks];
         for line in it {
             for (c, stack_index) in line.chars().zip(stacks_index.iter()) {
@@ -45,20 +55,27 @@ pub fn generator(input: &str) -> Input {
         }
         stacks
     });
-    let pattern = regex::Regex::new(r#"move (\d+) from (\d+) to (\d+)"#).unwrap();
+    let num_stacks = arrangement.0.len();
     let commands = commands
-        .iter()
+        .lines()
         .skip_while(|l| l.is_empty())
         .map(|line| {
-            let captures = pattern.captures(line).unwrap();
-            Command {
-                amount: captures[1].parse::<usize>().unwrap(),
-                from: captures[2].parse::<usize>().unwrap() - 1,
-                to: captures[3].parse::<usize>().unwrap() - 1,
-            }
+            let captures = COMMAND.captures(input, line, "move N from N to N")?;
+            let parse_stack = |text: &str| {
+                let expected = format!("a stack number between 1 and {}", num_stacks);
+                match integer::<usize>(input, text, &expected)? {
+                    stack @ 1.. if stack <= num_stacks => Ok(stack - 1),
+                    _ => Err(ParseError::new(input, text, expected)),
+                }
+            };
+            Ok(Command {
+                amount: integer(input, &captures[1], "an amount")?,
+                from: parse_stack(&captures[2])?,
+                to: parse_stack(&captures[3])?,
+            })
         })
-        .collect_vec();
-    (arrangement, commands)
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok((arrangement, commands))
 }
 
 impl Display for Arrangement {
@@ -137,3 +154,54 @@ pub fn part_2((arrangement, commands): &Input) -> String {
     }
     arrangement.code()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "    [D]
+[N] [C]
+[Z] [M] [P]
+ 1   2   3
+
+move 1 from 2 to 1
+move 3 from 1 to 3
+move 2 from 2 to 1
+move 1 from 1 to 2
+";
+
+    #[test]
+    fn part_1_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part_1(&input), "CMZ");
+    }
+
+    #[test]
+    fn part_2_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part_2(&input), "MCD");
+    }
+
+    #[test]
+    fn arrangement_display() {
+        let (mut arrangement, commands) = generator(EXAMPLE).unwrap();
+        assert_eq!(
+            arrangement.to_string(),
+            ["    [D]    ", "[N] [C]    ", "[Z] [M] [P]", " 1   2   3 "].join("\n")
+        );
+        for command in commands.iter() {
+            arrangement.apply_command(command, true);
+        }
+        assert_eq!(
+            arrangement.to_string(),
+            [
+                "        [Z]",
+                "        [N]",
+                "        [D]",
+                "[C] [M] [P]",
+                " 1   2   3 ",
+            ]
+            .join("\n")
+        );
+    }
+}
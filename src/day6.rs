@@ -1,5 +1,15 @@
-pub fn generator(input: &str) -> String {
-    input.trim().to_owned()
+use crate::error::ParseError;
+use crate::parsing::check_chars;
+
+pub fn generator(input: &str) -> Result<String, ParseError> {
+    let signal = input.trim();
+    check_chars(
+        input,
+        signal,
+        |c| c.is_ascii_lowercase(),
+        "a lowercase letter",
+    )?;
+    Ok(signal.to_owned())
 }
 
 fn marker_end_position(signal: &str, length: usize) -> usize {
@@ -25,3 +35,33 @@ pub fn part_1(input: &str) -> usize {
 pub fn part_2(input: &str) -> usize {
     marker_end_position(input, 14)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn part_1_examples() {
+        for (signal, expected) in [
+            ("mjqjpqmgbljsphdztnvjfqwrcgsmlb", 7),
+            ("bvwbjplbgvbhsrlpgjmjqm", 5),
+            ("nppdvjthqldpwncqszvftbrmjlhg", 6),
+            ("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg", 10),
+            ("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", 11),
+        ] {
+            assert_eq!(part_1(&generator(signal).unwrap()), expected, "{}", signal);
+        }
+    }
+
+    #[test]
+    fn part_2_examples() {
+        for (signal, expected) in [
+            ("mjqjpqmgbljsphdztnvjfqwrcgsmlb", 19),
+            ("nppdvjthqldpwncqszvftbrmjlhg", 23),
+            ("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg", 29),
+            ("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", 26),
+        ] {
+            assert_eq!(part_2(&generator(signal).unwrap()), expected, "{}", signal);
+        }
+    }
+}
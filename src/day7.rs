@@ -3,6 +3,9 @@ use std::cmp::Reverse;
 use hashbrown::HashMap;
 use itertools::Itertools;
 
+use crate::error::ParseError;
+use crate::parsing::integer;
+
 #[derive(Debug)]
 pub enum Instruction {
     CdRoot,
@@ -17,18 +20,24 @@ pub enum DirItem {
     Subdirectory(String),
 }
 
-pub fn generator(input: &str) -> Vec<Instruction> {
+pub fn generator(input: &str) -> Result<Vec<Instruction>, ParseError> {
     let mut result = Vec::new();
     let mut dir_content: Option<Vec<DirItem>> = None;
     for line in input.lines() {
         let mut parts = line.split(' ');
-        match parts.next().unwrap() {
+        let mut next_part = |expected: &str| {
+            parts
+                .next()
+                .ok_or_else(|| ParseError::new(input, &line[line.len()..], expected))
+        };
+        let first = next_part("a command or a listing")?;
+        match first {
             "$" => {
                 if let Some(dir_content) = dir_content.take() {
                     result.push(Instruction::Dir(dir_content));
                 }
-                match parts.next().unwrap() {
-                    "cd" => result.push(match parts.next().unwrap() {
+                match next_part("cd or ls")? {
+                    "cd" => result.push(match next_part("a directory")? {
                         "/" => Instruction::CdRoot,
                         ".." => Instruction::CdUp,
                         dir => Instruction::Cd(dir.to_owned()),
@@ -36,28 +45,38 @@ pub fn generator(input: &str) -> Vec<Instruction> {
                     "ls" => {
                         dir_content = Some(Vec::new());
                     }
-                    unknown_command => panic!("Cannot handle {}", unknown_command),
+                    unknown_command => {
+                        return Err(ParseError::new(input, unknown_command, "cd or ls"));
+                    }
                 }
             }
             "dir" => {
+                let dirname = next_part("a directory name")?;
                 dir_content
                     .as_mut()
-                    .unwrap()
-                    .push(DirItem::Subdirectory(parts.next().unwrap().to_owned()));
+                    .ok_or_else(|| ParseError::new(input, line, "a command"))?
+                    .push(DirItem::Subdirectory(dirname.to_owned()));
             }
             size => {
-                dir_content.as_mut().unwrap().push(DirItem::File {
-                    size: size.parse().unwrap(),
-                    name: parts.next().unwrap().to_owned(),
-                });
+                let size = integer(input, size, "a file size")?;
+                let name = next_part("a file name")?;
+                dir_content
+                    .as_mut()
+                    .ok_or_else(|| ParseError::new(input, line, "a command"))?
+                    .push(DirItem::File {
+                        size,
+                        name: name.to_owned(),
+                    });
             }
         }
-        assert!(parts.next().is_none());
+        if let Some(extra) = parts.next() {
+            return Err(ParseError::new(input, extra, "end of line"));
+        }
     }
     if let Some(dir_content) = dir_content {
         result.push(Instruction::Dir(dir_content));
     }
-    result
+    Ok(result)
 }
 
 #[derive(Debug)]
@@ -122,19 +141,46 @@ impl FilesystemDirectorySizes {
     }
 }
 
+#[derive(Debug, Clone)]
+pub struct Params {
+    pub small_directory_limit: usize,
+    pub total_disk_capacity: usize,
+    pub required_capacity: usize,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            small_directory_limit: 100_000,
+            total_disk_capacity: 70_000_000,
+            required_capacity: 30_000_000,
+        }
+    }
+}
+
 pub fn part_1(input: &[Instruction]) -> usize {
+    part_1_with(input, &Params::default())
+}
+
+pub fn part_1_with(input: &[Instruction], params: &Params) -> usize {
     let filesystem = Filesystem::build_from_instructions(input);
     let sizes = FilesystemDirectorySizes::calc_from(&filesystem);
-    sizes.sizes.values().filter(|&&size| size <= 100_000).sum()
+    sizes
+        .sizes
+        .values()
+        .filter(|&&size| size <= params.small_directory_limit)
+        .sum()
 }
 
 pub fn part_2(input: &[Instruction]) -> usize {
+    part_2_with(input, &Params::default())
+}
+
+pub fn part_2_with(input: &[Instruction], params: &Params) -> usize {
     let filesystem = Filesystem::build_from_instructions(input);
     let sizes = FilesystemDirectorySizes::calc_from(&filesystem);
-    let total_disk_capacity = 70_000_000;
-    let currently_unused_capacity = total_disk_capacity - sizes.sizes[&vec![]];
-    let required_capacity = 30_000_000;
-    let need_to_free = required_capacity - currently_unused_capacity;
+    let currently_unused_capacity = params.total_disk_capacity - sizes.sizes[&vec![]];
+    let need_to_free = params.required_capacity - currently_unused_capacity;
     sizes
         .sizes
         .values()
@@ -143,3 +189,46 @@ pub fn part_2(input: &[Instruction]) -> usize {
         .min()
         .unwrap()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+$ cd /
+$ ls
+dir a
+14848514 b.txt
+8504156 c.dat
+dir d
+$ cd a
+$ ls
+dir e
+29116 f
+2557 g
+62596 h.lst
+$ cd e
+$ ls
+584 i
+$ cd ..
+$ cd ..
+$ cd d
+$ ls
+4060174 j
+8033020 d.log
+5626152 d.ext
+7214296 k
+";
+
+    #[test]
+    fn part_1_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part_1(&input), 95437);
+    }
+
+    #[test]
+    fn part_2_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part_2(&input), 24933642);
+    }
+}
@@ -3,6 +3,9 @@ use std::iter::from_fn;
 
 use itertools::Itertools;
 
+use crate::error::ParseError;
+use crate::parsing::grid;
+
 #[derive(Debug)]
 pub struct Forest {
     cols: usize,
@@ -22,30 +25,15 @@ impl Display for Forest {
     }
 }
 
-pub fn generator(input: &str) -> Forest {
-    let mut cols = 0;
-    let mut cur_col = 0;
-    let heights = input
-        .chars()
-        .filter_map(|c| {
-            if c == '\n' {
-                cols = cur_col;
-                cur_col = 0;
-                None
-            } else if ('0'..='9').contains(&c) {
-                cur_col += 1;
-                Some(c as usize - '0' as usize)
-            } else {
-                panic!("Bad char {:?}", c);
-            }
-        })
-        .collect_vec();
-    let rows = heights.len() / cols;
-    Forest {
-        cols,
-        rows,
-        heights,
-    }
+pub fn generator(input: &str) -> Result<Forest, ParseError> {
+    let heights = grid(input, input, "trees", "a tree height", |c| {
+        c.to_digit(10).map(|height| height as usize)
+    })?;
+    Ok(Forest {
+        cols: heights.cols,
+        rows: heights.rows,
+        heights: heights.values,
+    })
 }
 
 impl Forest {
@@ -119,3 +107,28 @@ pub fn part_2(forest: &Forest) -> usize {
         .max()
         .unwrap()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+30373
+25512
+65332
+33549
+35390
+";
+
+    #[test]
+    fn part_1_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part_1(&input), 21);
+    }
+
+    #[test]
+    fn part_2_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part_2(&input), 8);
+    }
+}
@@ -1,5 +1,8 @@
 use hashbrown::HashSet;
 
+use crate::error::ParseError;
+use crate::parsing::{integer, pair};
+
 #[derive(Debug)]
 pub struct MovementInstruction {
     amount: usize,
@@ -25,20 +28,20 @@ impl Direcetion {
     }
 }
 
-pub fn generator(input: &str) -> Vec<MovementInstruction> {
+pub fn generator(input: &str) -> Result<Vec<MovementInstruction>, ParseError> {
     input
         .lines()
         .map(|line| {
-            let mut parts = line.split(' ');
-            let direction = match parts.next().unwrap() {
+            let (direction, amount) = pair(input, line, " ", "a direction and an amount")?;
+            let direction = match direction {
                 "L" => Direcetion::Left,
                 "R" => Direcetion::Right,
                 "U" => Direcetion::Up,
                 "D" => Direcetion::Down,
-                _ => panic!(),
+                _ => return Err(ParseError::new(input, direction, "L, R, U or D")),
             };
-            let amount = parts.next().unwrap().parse().unwrap();
-            MovementInstruction { amount, direction }
+            let amount = integer(input, amount, "an amount")?;
+            Ok(MovementInstruction { amount, direction })
         })
         .collect()
 }
@@ -99,3 +102,31 @@ pub fn part_1(input: &[MovementInstruction]) -> usize {
 pub fn part_2(input: &[MovementInstruction]) -> usize {
     calc_for(input, 10)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+R 4
+U 4
+L 3
+D 1
+R 4
+D 1
+L 5
+R 2
+";
+
+    #[test]
+    fn part_1_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part_1(&input), 13);
+    }
+
+    #[test]
+    fn part_2_example() {
+        let input = generator(EXAMPLE).unwrap();
+        assert_eq!(part_2(&input), 1);
+    }
+}
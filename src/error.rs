@@ -0,0 +1,89 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub text: String,
+    pub expected: String,
+}
+
+impl ParseError {
+    /// `part` must be a slice of `input` - its position inside `input` is what gets reported.
+    pub fn new(input: &str, part: &str, expected: impl Into<String>) -> Self {
+        let offset = (part.as_ptr() as usize).wrapping_sub(input.as_ptr() as usize);
+        debug_assert!(
+            offset <= input.len(),
+            "{:?} is not a part of the input",
+            part
+        );
+        let offset = offset.min(input.len());
+        let before = &input[..offset];
+        let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+        Self {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            text: part.to_owned(),
+            expected: expected.into(),
+        }
+    }
+
+    pub fn at_end(input: &str, expected: impl Into<String>) -> Self {
+        Self::new(input, &input[input.len()..], expected)
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: expected {}, found ",
+            self.line, self.column, self.expected
+        )?;
+        if self.text.is_empty() {
+            write!(f, "nothing")
+        } else {
+            write!(f, "{:?}", self.text)
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Why an input that parsed fine still has no answer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SolveError {
+    /// No path leads to the goal.
+    Unreachable,
+    /// Every position in the search area is covered.
+    NoGapFound,
+    /// The input lacks the thing the puzzle starts from, described by the string.
+    MissingStart(String),
+    /// There is nothing for the sand to rest on.
+    NoRocks,
+    /// The input has none of the things the puzzle is about, described by the string.
+    Empty(String),
+    /// The input does not pin down a single answer, for the reason in the string.
+    Unsolvable(String),
+}
+
+impl Display for SolveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SolveError::Unreachable => write!(f, "the goal cannot be reached"),
+            SolveError::NoGapFound => write!(f, "every position in the search area is covered"),
+            SolveError::MissingStart(what) => write!(f, "there is no {} to start from", what),
+            SolveError::NoRocks => write!(f, "the cave has no rocks"),
+            SolveError::Empty(what) => write!(f, "there are no {}", what),
+            SolveError::Unsolvable(reason) => write!(f, "there is no single answer: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for SolveError {}
+
+pub fn parse_part<T: FromStr>(input: &str, part: &str, expected: &str) -> Result<T, ParseError> {
+    part.parse()
+        .map_err(|_| ParseError::new(input, part, expected))
+}
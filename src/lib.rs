@@ -1,28 +1,66 @@
-mod bfs;
-mod vmatrix;
+pub mod answer;
+pub mod batch;
+pub mod bench;
+pub mod bfs;
+pub mod budget;
+pub mod check;
+pub mod error;
+pub mod normalize;
+pub mod parsing;
+pub mod random_input;
+pub mod runner;
+pub mod solution;
+pub mod stats;
+pub mod visualize;
+pub mod vmatrix;
 
+#[cfg(feature = "day1")]
 pub mod day1;
+#[cfg(feature = "day10")]
 pub mod day10;
+#[cfg(feature = "day11")]
 pub mod day11;
+#[cfg(feature = "day12")]
 pub mod day12;
+#[cfg(feature = "day13")]
 pub mod day13;
+#[cfg(feature = "day14")]
 pub mod day14;
+#[cfg(feature = "day15")]
 pub mod day15;
+#[cfg(feature = "day16")]
 pub mod day16;
+#[cfg(feature = "day17")]
 pub mod day17;
+#[cfg(feature = "day18")]
 pub mod day18;
+#[cfg(feature = "day19")]
 pub mod day19;
+#[cfg(feature = "day2")]
 pub mod day2;
+#[cfg(feature = "day20")]
 pub mod day20;
+#[cfg(feature = "day21")]
 pub mod day21;
+#[cfg(feature = "day22")]
 pub mod day22;
+#[cfg(feature = "day23")]
 pub mod day23;
+#[cfg(feature = "day24")]
 pub mod day24;
+#[cfg(feature = "day25")]
 pub mod day25;
+#[cfg(feature = "day3")]
 pub mod day3;
+#[cfg(feature = "day4")]
 pub mod day4;
+#[cfg(feature = "day5")]
 pub mod day5;
+#[cfg(feature = "day6")]
 pub mod day6;
+#[cfg(feature = "day7")]
 pub mod day7;
+#[cfg(feature = "day8")]
 pub mod day8;
+#[cfg(feature = "day9")]
 pub mod day9;
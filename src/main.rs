@@ -1,32 +1,930 @@
-#![recursion_limit = "256"]
-
-use aoc_2022::*;
-
-aoc_main::main! {
-    year 2022;
-    day1 : generator => part_1, part_2;
-    day2 : generator => part_1, part_2;
-    day3 : generator => part_1, part_2;
-    day4 : generator => part_1, part_2;
-    day5 : generator => part_1, part_2;
-    day6 : generator => part_1, part_2;
-    day7 : generator => part_1, part_2;
-    day8 : generator => part_1, part_2;
-    day9 : generator => part_1, part_2;
-    day10 : generator => part_1, part_2;
-    day11 : generator => part_1, part_2;
-    day12 : generator => part_1, part_2;
-    day13 : generator => part_1, part_2;
-    day14 : generator => part_1, part_2;
-    day15 : generator => part_1, part_2;
-    day16 : generator => part_1, part_2;
-    day17 : generator => part_1, part_2;
-    day18 : generator => part_1, part_2;
-    day19 : generator => part_1, part_2;
-    day20 : generator => part_1, part_2;
-    day21 : generator => part_1, part_2;
-    day22 : generator => part_1, part_2;
-    day23 : generator => part_1, part_2;
-    day24 : generator => part_1, part_2;
-    day25 : generator => part_1, part_2;
+use std::fmt::Display;
+use std::fs::read_to_string;
+use std::io::Read;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use aoc_main::clap::{value_parser, Arg, ArgAction, ArgMatches, Command, ValueHint};
+use aoc_main::colored::{Color, ColoredString, Colorize};
+use aoc_main::utils::Line;
+use serde_json::json;
+
+use aoc_2022::answer::Answer;
+use aoc_2022::batch::{load_inputs, BatchInput, ANSWERS_EXTENSION};
+use aoc_2022::bench::{bench_solution, compare, Baseline, Comparison, DEFAULT_BASELINE_PATH};
+use aoc_2022::budget::Budget;
+use aoc_2022::check::{self, CheckOutcome, CheckSummary, ExpectedAnswers, DEFAULT_ANSWERS_DIR};
+use aoc_2022::normalize::normalize;
+use aoc_2022::random_input;
+use aoc_2022::runner::{run_parallel, run_solution, DayRun};
+use aoc_2022::solution::{self, Part, Solution, SOLUTIONS};
+use aoc_2022::stats;
+use aoc_2022::visualize::{self, AnsiPlayer, FrameSink, PpmSequence};
+
+const YEAR: u16 = 2022;
+
+/// Pixels per cell in the exported images.
+const PPM_SCALE: usize = 4;
+
+fn args() -> Command {
+    Command::new(format!("Advent of Code {YEAR}"))
+        .about(format!(
+            "Main page of the event: https://adventofcode.com/{YEAR}/"
+        ))
+        .arg(
+            Arg::new("stdin")
+                .short('i')
+                .long("stdin")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("file")
+                .help("Read input from stdin instead of downloading it"),
+        )
+        .arg(
+            Arg::new("file")
+                .short('f')
+                .long("file")
+                .conflicts_with("stdin")
+                .value_hint(ValueHint::FilePath)
+                .value_parser(value_parser!(PathBuf))
+                .help("Read input from file instead of downloading it"),
+        )
+        .arg(
+            Arg::new("random")
+                .long("random")
+                .value_name("SEED")
+                .value_parser(value_parser!(u64))
+                .conflicts_with_all(["stdin", "file", "check", "record"])
+                .help("Run on a randomly generated input instead of downloading it"),
+        )
+        .arg(
+            Arg::new("size")
+                .long("size")
+                .value_name("N")
+                .value_parser(value_parser!(usize))
+                .default_value("20")
+                .requires("random")
+                .help("The size of the randomly generated input. Its meaning depends on the day"),
+        )
+        .arg(
+            Arg::new("days")
+                .short('d')
+                .long("day")
+                .value_name("day num")
+                .action(ArgAction::Append)
+                .value_parser(value_parser!(u8))
+                .help("Days to execute. By default the latest implemented day will run"),
+        )
+        .arg(
+            Arg::new("check")
+                .short('c')
+                .long("check")
+                .action(ArgAction::SetTrue)
+                .help("Compare the answers with the recorded ones"),
+        )
+        .arg(
+            Arg::new("record")
+                .long("record")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("stdin")
+                .help("Record the answers as the accepted ones"),
+        )
+        .arg(
+            Arg::new("answers")
+                .long("answers")
+                .value_name("DIR")
+                .value_hint(ValueHint::DirPath)
+                .value_parser(value_parser!(PathBuf))
+                .default_value(DEFAULT_ANSWERS_DIR)
+                .help("Where the answers of the downloaded inputs are recorded"),
+        )
+        .arg(
+            Arg::new("bench")
+                .short('b')
+                .long("bench")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["check", "record"])
+                .help("Benchmark the generator and the parts and compare them with the baseline"),
+        )
+        .arg(
+            Arg::new("iterations")
+                .long("iterations")
+                .value_name("N")
+                .value_parser(value_parser!(u64).range(1..))
+                .default_value("10")
+                .help("How many times to run each function when benchmarking"),
+        )
+        .arg(
+            Arg::new("baseline")
+                .long("baseline")
+                .value_name("FILE")
+                .value_hint(ValueHint::FilePath)
+                .value_parser(value_parser!(PathBuf))
+                .default_value(DEFAULT_BASELINE_PATH)
+                .help("The benchmark timings to compare with"),
+        )
+        .arg(
+            Arg::new("save-baseline")
+                .long("save-baseline")
+                .action(ArgAction::SetTrue)
+                .requires("bench")
+                .help("Store the benchmark timings in the baseline file"),
+        )
+        .arg(
+            Arg::new("threshold")
+                .long("threshold")
+                .value_name("PERCENT")
+                .value_parser(value_parser!(f64))
+                .default_value("10")
+                .help("How much slower than the baseline a function may get"),
+        )
+        .arg(
+            Arg::new("parallel")
+                .short('p')
+                .long("parallel")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["bench", "check", "record"])
+                .help("Solve the days concurrently and print a summary table. Runs all days unless -d is given"),
+        )
+        .arg(
+            Arg::new("threads")
+                .long("threads")
+                .value_name("N")
+                .value_parser(value_parser!(u64).range(1..))
+                .help("How many worker threads --parallel and --batch use. Defaults to the number of CPUs"),
+        )
+        .arg(
+            Arg::new("batch")
+                .long("batch")
+                .value_name("DIR")
+                .value_hint(ValueHint::DirPath)
+                .value_parser(value_parser!(PathBuf))
+                .conflicts_with_all(["stdin", "file", "random", "all", "bench", "check", "record", "parallel"])
+                .help("Run the day on every input file in the directory. Expected answers are read from files with the same name and the `answers` extension"),
+        )
+        .arg(
+            Arg::new("json")
+                .long("json")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["bench", "batch", "check", "record"])
+                .help("Print the answers and timings as a single JSON document"),
+        )
+        .arg(
+            Arg::new("stats")
+                .long("stats")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["bench", "batch"])
+                .help("Print what the searches did in each part: nodes expanded, pruned branches, recursion depth, memo hits and queue size"),
+        )
+        .arg(
+            Arg::new("time-budget")
+                .long("time-budget")
+                .value_name("MS")
+                .value_parser(value_parser!(u64))
+                .conflicts_with_all(["bench", "record", "visualize"])
+                .help("Stop the long searches of a part after this long and report the best answer found so far"),
+        )
+        .arg(
+            Arg::new("node-budget")
+                .long("node-budget")
+                .value_name("N")
+                .value_parser(value_parser!(u64))
+                .conflicts_with_all(["bench", "record", "visualize"])
+                .help("Stop the long searches of a part after expanding this many nodes and report the best answer found so far"),
+        )
+        .arg(
+            Arg::new("visualize")
+                .long("visualize")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["bench", "batch", "check", "record", "parallel", "json"])
+                .help("Animate the simulation of the day in the terminal"),
+        )
+        .arg(
+            Arg::new("part")
+                .long("part")
+                .value_name("PART")
+                .value_parser(value_parser!(u8).range(1..=2))
+                .default_value("1")
+                .help("Which part --visualize shows"),
+        )
+        .arg(
+            Arg::new("frames")
+                .long("frames")
+                .value_name("DIR")
+                .value_hint(ValueHint::DirPath)
+                .value_parser(value_parser!(PathBuf))
+                .requires("visualize")
+                .help("Write the frames of --visualize as numbered PPM images instead"),
+        )
+        .arg(
+            Arg::new("every")
+                .long("every")
+                .value_name("N")
+                .value_parser(value_parser!(u64).range(1..))
+                .default_value("1")
+                .help("Only draw every Nth step of --visualize"),
+        )
+        .arg(
+            Arg::new("delay")
+                .long("delay")
+                .value_name("MS")
+                .value_parser(value_parser!(u64))
+                .default_value("50")
+                .help("How long each frame of the terminal animation is shown"),
+        )
+        .arg(
+            Arg::new("verbose")
+                .short('v')
+                .long("verbose")
+                .action(ArgAction::SetTrue)
+                .help("Report how the inputs were normalized: byte order marks, CRLF line endings and trailing blank lines"),
+        )
+        .arg(
+            Arg::new("all")
+                .short('a')
+                .long("all")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("days")
+                .help("Run all days"),
+        )
+}
+
+fn selected_solutions(opt: &ArgMatches) -> Vec<&'static dyn Solution> {
+    if let Some(days) = opt.get_many::<u8>("days") {
+        let (implemented, ignored): (Vec<_>, Vec<_>) = days
+            .map(|day| (*day, solution::get(*day)))
+            .partition(|(_, s)| s.is_some());
+        if !ignored.is_empty() {
+            let ignored = ignored
+                .iter()
+                .map(|(day, _)| day.to_string())
+                .collect::<Vec<_>>();
+            eprintln!(r"/!\ Ignoring unimplemented days: {}", ignored.join(", "));
+        }
+        implemented.into_iter().filter_map(|(_, s)| s).collect()
+    } else if opt.get_flag("all") {
+        SOLUTIONS.to_vec()
+    } else {
+        vec![*SOLUTIONS.last().expect("No day implemenations found")]
+    }
+}
+
+/// Inputs are normalized when they are parsed. In verbose mode, this tells what that changes.
+fn report_normalization(opt: &ArgMatches, name: impl Display, data: &str) {
+    if !opt.get_flag("verbose") {
+        return;
+    }
+    let (_, normalization) = normalize(data);
+    eprintln!(
+        "{}",
+        format!("{}: input {}", name, normalization).bright_black()
+    );
+}
+
+fn try_read_input(opt: &ArgMatches, day: u8) -> Result<String, String> {
+    let data = if opt.get_flag("stdin") {
+        let mut data = String::new();
+        std::io::stdin()
+            .read_to_string(&mut data)
+            .map_err(|err| format!("failed to read from stdin: {}", err))?;
+        data
+    } else if let Some(path) = opt.get_one::<PathBuf>("file") {
+        read_to_string(path).map_err(|err| format!("failed to read {}: {}", path.display(), err))?
+    } else if let Some(&seed) = opt.get_one::<u64>("random") {
+        let size = *opt.get_one::<usize>("size").unwrap();
+        random_input::generate(day, seed, size)
+            .ok_or_else(|| format!("no random input generator for day {}", day))?
+    } else {
+        aoc_main::input::get_input(YEAR, day)
+            .map_err(|err| format!("could not fetch the input of day {}: {}", day, err))?
+    };
+    report_normalization(opt, format!("Day {}", day), &data);
+    Ok(data)
+}
+
+fn read_input(opt: &ArgMatches, day: u8) -> String {
+    try_read_input(opt, day).unwrap_or_else(|err| panic!("{}", err))
+}
+
+/// Answers of an input given with `--file` are kept next to it, in a file with the `answers`
+/// extension. Inputs from stdin or random inputs have nowhere to keep their answers.
+fn answers_path(opt: &ArgMatches, day: u8) -> Option<PathBuf> {
+    if opt.get_flag("stdin") || opt.contains_id("random") {
+        None
+    } else if let Some(path) = opt.get_one::<PathBuf>("file") {
+        Some(path.with_extension(ANSWERS_EXTENSION))
+    } else {
+        Some(check::answers_path(
+            opt.get_one::<PathBuf>("answers").unwrap(),
+            day,
+        ))
+    }
+}
+
+fn load_expected_answers(path: Option<&PathBuf>) -> ExpectedAnswers {
+    let Some(path) = path else {
+        return ExpectedAnswers::default();
+    };
+    ExpectedAnswers::load(path)
+        .unwrap_or_else(|err| panic!("failed to load answers from {}: {}", path.display(), err))
+}
+
+fn budget(opt: &ArgMatches) -> Budget {
+    Budget {
+        time_limit: opt
+            .get_one::<u64>("time-budget")
+            .copied()
+            .map(Duration::from_millis),
+        max_nodes: opt.get_one::<u64>("node-budget").copied(),
+    }
+}
+
+/// Answers cut short by the budget are marked as such.
+fn answer_text(answer: &Answer, is_final: bool) -> String {
+    if is_final {
+        answer.to_string()
+    } else {
+        format!("{} (best so far)", answer)
+    }
+}
+
+fn answer_state(answer: &Answer, is_final: bool, outcome: Option<&CheckOutcome>) -> ColoredString {
+    let answer = answer_text(answer, is_final);
+    match outcome {
+        None if is_final => answer.normal(),
+        None => answer.yellow(),
+        Some(CheckOutcome::Pass) => answer.to_string().green(),
+        Some(CheckOutcome::Fail { expected }) => {
+            format!("{} (expected {})", answer, expected).red()
+        }
+        Some(CheckOutcome::Missing) => format!("{} (no recorded answer)", answer).yellow(),
+    }
+}
+
+fn run_day(
+    solution: &dyn Solution,
+    data: &str,
+    expected: Option<&ExpectedAnswers>,
+    summary: &mut CheckSummary,
+    budget: &Budget,
+    show_stats: bool,
+) -> Option<[Answer; 2]> {
+    println!("Day {}", solution.day());
+
+    let start = Instant::now();
+    let result = solution.parse(data);
+    let elapsed = start.elapsed();
+    match result {
+        Ok(input) => {
+            println!("  - {}", Line::new("generator").with_duration(elapsed));
+            let answers = Part::ALL.map(|part| {
+                let start = Instant::now();
+                let (result, part_stats) =
+                    stats::collect(|| solution.run_part_within(part, &input, budget));
+                let elapsed = start.elapsed();
+                let line = Line::new(part.name()).with_duration(elapsed);
+                let answer = match result {
+                    Ok(outcome) => {
+                        let is_final = outcome.is_final();
+                        let answer = outcome.value();
+                        let outcome = expected.map(|expected| expected.check(part, &answer));
+                        if let Some(outcome) = &outcome {
+                            summary.add(outcome);
+                        }
+                        println!(
+                            "  - {}",
+                            line.with_state(answer_state(&answer, is_final, outcome.as_ref()))
+                        );
+                        Some(answer)
+                    }
+                    Err(err) => {
+                        if expected.is_some() {
+                            summary.failed += 1;
+                        }
+                        println!("  - {}", line.with_state(err.to_string().red()));
+                        None
+                    }
+                };
+                if show_stats && !part_stats.is_empty() {
+                    println!("    {}", part_stats.to_string().bright_black());
+                }
+                answer
+            });
+            let [Some(part_1), Some(part_2)] = answers else {
+                return None;
+            };
+            Some([part_1, part_2])
+        }
+        Err(err) => {
+            println!(
+                "  - {}",
+                Line::new("generator")
+                    .with_duration(elapsed)
+                    .with_state(err.to_string().red())
+            );
+            for part in Part::ALL {
+                if expected.is_some() {
+                    summary.failed += 1;
+                }
+                println!(
+                    "  - {}",
+                    Line::new(part.name()).with_state("skipped".bright_black())
+                );
+            }
+            None
+        }
+    }
+}
+
+/// Returns whether any function got slower than the baseline allows.
+fn bench_day(
+    solution: &dyn Solution,
+    data: &str,
+    opt: &ArgMatches,
+    baseline: &mut Baseline,
+) -> bool {
+    println!("Day {}", solution.day());
+    let iterations = *opt.get_one::<u64>("iterations").unwrap() as usize;
+    let threshold = opt.get_one::<f64>("threshold").unwrap() / 100.0;
+
+    let timings = match bench_solution(solution, data, iterations) {
+        Ok(timings) => timings,
+        Err(err) => {
+            println!(
+                "  - {}",
+                Line::new("generator").with_state(err.to_string().red())
+            );
+            return false;
+        }
+    };
+    let mut any_regression = false;
+    for (stage, timing) in timings {
+        let state = match compare(baseline.get(solution.name(), stage), &timing, threshold) {
+            Comparison::NoBaseline => "no baseline".bright_black(),
+            Comparison::WithinThreshold { ratio } => {
+                format!("{:+.1}%", (ratio - 1.0) * 100.0).green()
+            }
+            Comparison::Regression { ratio } => {
+                any_regression = true;
+                format!("{:+.1}% slower than the baseline", (ratio - 1.0) * 100.0).red()
+            }
+        };
+        println!(
+            "  - {}",
+            Line::new(format!("{} ({})", stage.name(), timing)).with_state(state)
+        );
+        if opt.get_flag("save-baseline") {
+            baseline.set(solution.name(), stage, timing);
+        }
+    }
+    any_regression
+}
+
+#[derive(Clone, Copy)]
+enum Align {
+    Left,
+    Right,
+}
+
+#[derive(Default)]
+struct Cell {
+    text: String,
+    color: Option<Color>,
+}
+
+impl Cell {
+    fn colored(text: impl Into<String>, color: Color) -> Self {
+        Self {
+            text: text.into(),
+            color: Some(color),
+        }
+    }
+}
+
+impl From<String> for Cell {
+    fn from(text: String) -> Self {
+        Self { text, color: None }
+    }
+}
+
+impl From<&str> for Cell {
+    fn from(text: &str) -> Self {
+        text.to_owned().into()
+    }
+}
+
+/// Cells with multiple lines continue on the following table lines.
+struct Table {
+    columns: Vec<(&'static str, Align)>,
+    rows: Vec<Vec<Cell>>,
+}
+
+impl Table {
+    fn new(columns: impl IntoIterator<Item = (&'static str, Align)>) -> Self {
+        Self {
+            columns: columns.into_iter().collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    fn add_row(&mut self, row: Vec<Cell>) {
+        assert_eq!(row.len(), self.columns.len());
+        self.rows.push(row);
+    }
+
+    fn print(&self) {
+        let lines_of = |cell: &Cell| -> Vec<String> {
+            cell.text
+                .trim_matches('\n')
+                .lines()
+                .map(str::to_owned)
+                .collect()
+        };
+        let mut widths = self
+            .columns
+            .iter()
+            .map(|(title, _)| title.chars().count())
+            .collect::<Vec<_>>();
+        for row in self.rows.iter() {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                for line in lines_of(cell) {
+                    *width = (*width).max(line.chars().count());
+                }
+            }
+        }
+        let pad = |text: &str, width: usize, align: Align| {
+            let padding = " ".repeat(width - text.chars().count());
+            match align {
+                Align::Left => format!("{}{}", text, padding),
+                Align::Right => format!("{}{}", padding, text),
+            }
+        };
+
+        let header = self
+            .columns
+            .iter()
+            .zip(widths.iter())
+            .map(|((title, align), width)| pad(title, *width, *align))
+            .collect::<Vec<_>>();
+        println!("{}", header.join("  ").trim_end().bold());
+        for row in self.rows.iter() {
+            let cells_lines = row.iter().map(lines_of).collect::<Vec<_>>();
+            let num_lines = cells_lines.iter().map(Vec::len).max().unwrap_or(0).max(1);
+            for i in 0..num_lines {
+                let line = row
+                    .iter()
+                    .zip(cells_lines.iter())
+                    .zip(self.columns.iter().zip(widths.iter()))
+                    .map(|((cell, lines), ((_, align), width))| {
+                        let text = pad(lines.get(i).map_or("", String::as_str), *width, *align);
+                        match cell.color {
+                            Some(color) => text.color(color).to_string(),
+                            None => text,
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                println!("{}", line.join("  ").trim_end());
+            }
+        }
+    }
+}
+
+fn print_day_runs_table(day_runs: &[DayRun], wall_time: Duration, show_stats: bool) {
+    let mut columns = vec![
+        ("Day", Align::Right),
+        ("Part", Align::Left),
+        ("Answer", Align::Left),
+        ("Time", Align::Right),
+    ];
+    if show_stats {
+        columns.push(("Stats", Align::Left));
+    }
+    let mut table = Table::new(columns);
+    for day_run in day_runs {
+        let day = day_run.solution.day().to_string();
+        match &day_run.parts {
+            Ok(part_runs) => {
+                for part_run in part_runs {
+                    let mut row = vec![
+                        day.as_str().into(),
+                        part_run.part.name().into(),
+                        match &part_run.answer {
+                            Ok(answer) if part_run.is_final => answer.to_string().into(),
+                            Ok(answer) => Cell::colored(answer_text(answer, false), Color::Yellow),
+                            Err(err) => Cell::colored(err.to_string(), Color::Red),
+                        },
+                        format!("{:.2?}", part_run.duration).into(),
+                    ];
+                    if show_stats {
+                        row.push(Cell::colored(
+                            part_run.stats.to_string(),
+                            Color::BrightBlack,
+                        ));
+                    }
+                    table.add_row(row);
+                }
+            }
+            Err(err) => {
+                let mut row = vec![
+                    day.into(),
+                    "generator".into(),
+                    Cell::colored(err.to_string(), Color::Red),
+                    format!("{:.2?}", day_run.generator_duration).into(),
+                ];
+                if show_stats {
+                    row.push(Cell::default());
+                }
+                table.add_row(row);
+            }
+        }
+    }
+    table.print();
+    let total_time = day_runs
+        .iter()
+        .map(DayRun::total_duration)
+        .sum::<Duration>();
+    println!();
+    println!(
+        "Total: {:.2?} wall time, {:.2?} summed over all days",
+        wall_time, total_time
+    );
+}
+
+fn print_batch_table(inputs: &[BatchInput], day_runs: &[DayRun]) -> CheckSummary {
+    let mut summary = CheckSummary::default();
+    let mut table = Table::new([
+        ("File", Align::Left),
+        ("Parse", Align::Right),
+        ("part_1", Align::Left),
+        ("part_2", Align::Left),
+        ("Error", Align::Left),
+    ]);
+    for (input, day_run) in inputs.iter().zip(day_runs) {
+        let mut row = vec![
+            input.file_name().into(),
+            format!("{:.2?}", day_run.generator_duration).into(),
+        ];
+        let mut errors = Vec::new();
+        match &day_run.parts {
+            Ok(part_runs) => {
+                for part_run in part_runs {
+                    let answer = match &part_run.answer {
+                        Ok(answer) => answer,
+                        Err(err) => {
+                            errors.push(format!("{}: {}", part_run.part.name(), err));
+                            if input.expected.is_some() {
+                                summary.failed += 1;
+                            }
+                            row.push(Cell::colored("error", Color::Red));
+                            continue;
+                        }
+                    };
+                    let text = answer_text(answer, part_run.is_final);
+                    let Some(expected) = &input.expected else {
+                        row.push(match part_run.is_final {
+                            true => text.into(),
+                            false => Cell::colored(text, Color::Yellow),
+                        });
+                        continue;
+                    };
+                    let outcome = expected.check(part_run.part, answer);
+                    summary.add(&outcome);
+                    row.push(match outcome {
+                        CheckOutcome::Pass => Cell::colored(text, Color::Green),
+                        CheckOutcome::Fail { expected } => {
+                            Cell::colored(format!("{} (expected {})", text, expected), Color::Red)
+                        }
+                        CheckOutcome::Missing => Cell::colored(text, Color::Yellow),
+                    });
+                }
+            }
+            Err(err) => {
+                errors.push(err.to_string());
+                for _ in Part::ALL {
+                    if input.expected.is_some() {
+                        summary.failed += 1;
+                    }
+                    row.push(Cell::colored("skipped", Color::BrightBlack));
+                }
+            }
+        }
+        row.push(Cell::colored(errors.join("\n"), Color::Red));
+        table.add_row(row);
+    }
+    table.print();
+    summary
+}
+
+fn num_threads(opt: &ArgMatches) -> usize {
+    if let Some(num_threads) = opt.get_one::<u64>("threads") {
+        *num_threads as usize
+    } else {
+        std::thread::available_parallelism().map_or(1, |n| n.get())
+    }
+}
+
+/// The runs catch their panics and report them in the output, so the default hook would only
+/// print them a second time. Panics outside the runs, like failing to read the inputs, still print.
+fn without_panic_messages<T>(f: impl FnOnce() -> T) -> T {
+    let hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(|_| {}));
+    let result = f();
+    std::panic::set_hook(hook);
+    result
+}
+
+fn main() {
+    let opt = args().get_matches();
+    let solutions = if opt.get_flag("parallel") && !opt.contains_id("days") {
+        SOLUTIONS.to_vec()
+    } else {
+        selected_solutions(&opt)
+    };
+
+    if solutions.len() > 1 && (opt.get_flag("stdin") || opt.contains_id("file")) {
+        eprintln!(r"/!\ You are using a personalized output over several days which can");
+        eprintln!(r"    be missleading. If you only intend to run solutions for a");
+        eprintln!(r"    specific day, you can specify it by using the `-d DAY_NUM` flag.");
+    }
+
+    if let Some(dir) = opt.get_one::<PathBuf>("batch") {
+        let inputs = load_inputs(dir)
+            .unwrap_or_else(|err| panic!("failed to read inputs from {}: {}", dir.display(), err));
+        for input in inputs.iter() {
+            report_normalization(&opt, input.file_name(), &input.data);
+        }
+        let mut summary = CheckSummary::default();
+        for (i, solution) in solutions.into_iter().enumerate() {
+            if i != 0 {
+                println!()
+            }
+            println!("Day {}", solution.day());
+            let jobs = inputs
+                .iter()
+                .map(|input| (solution, input.data.clone()))
+                .collect();
+            let day_runs =
+                without_panic_messages(|| run_parallel(jobs, num_threads(&opt), &budget(&opt)));
+            summary += print_batch_table(&inputs, &day_runs);
+        }
+        if summary != CheckSummary::default() {
+            println!();
+            let line = format!("Check: {}", summary);
+            if summary.failed == 0 {
+                println!("{}", line.green());
+            } else {
+                println!("{}", line.red());
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    if opt.get_flag("parallel") || opt.get_flag("json") {
+        let jobs = solutions
+            .into_iter()
+            .map(|solution| {
+                let data = try_read_input(&opt, solution.day()).unwrap_or_else(|err| {
+                    if opt.get_flag("json") {
+                        // The output is still a JSON document, so whatever reads it sees the error.
+                        let json = json!({
+                            "year": YEAR,
+                            "error": { "kind": "input", "message": err },
+                        });
+                        println!("{:#}", json);
+                    } else {
+                        eprintln!("{}", err.red());
+                    }
+                    std::process::exit(1);
+                });
+                (solution, data)
+            })
+            .collect::<Vec<_>>();
+        let start = Instant::now();
+        let day_runs = without_panic_messages(|| {
+            if opt.get_flag("parallel") {
+                run_parallel(jobs, num_threads(&opt), &budget(&opt))
+            } else {
+                jobs.into_iter()
+                    .map(|(solution, data)| run_solution(solution, &data, &budget(&opt)))
+                    .collect()
+            }
+        });
+        let wall_time = start.elapsed();
+        if opt.get_flag("json") {
+            let json = json!({
+                "year": YEAR,
+                "days": day_runs.iter().map(DayRun::to_json).collect::<Vec<_>>(),
+                "wall_time_ns": wall_time.as_nanos() as u64,
+            });
+            println!("{:#}", json);
+        } else {
+            print_day_runs_table(&day_runs, wall_time, opt.get_flag("stats"));
+        }
+        return;
+    }
+
+    if opt.get_flag("visualize") {
+        let part = Part::ALL[*opt.get_one::<u8>("part").unwrap() as usize - 1];
+        let every = *opt.get_one::<u64>("every").unwrap() as usize;
+        for solution in solutions {
+            let data = read_input(&opt, solution.day());
+            let mut sink: Box<dyn FrameSink> =
+                match opt.get_one::<PathBuf>("frames") {
+                    Some(dir) => Box::new(PpmSequence::new(dir, PPM_SCALE).unwrap_or_else(|err| {
+                        panic!("failed to create {}: {}", dir.display(), err)
+                    })),
+                    None => {
+                        let delay = Duration::from_millis(*opt.get_one::<u64>("delay").unwrap());
+                        Box::new(AnsiPlayer::new(std::io::stdout().lock(), delay))
+                    }
+                };
+            match visualize::record(solution.day(), &data, part, every, sink.as_mut()) {
+                None => eprintln!(r"/!\ Day {} has no visualization", solution.day()),
+                Some(Ok(num_frames)) => {
+                    if let Some(dir) = opt.get_one::<PathBuf>("frames") {
+                        println!("Wrote {} frames to {}", num_frames, dir.display());
+                    }
+                }
+                Some(Err(err)) => panic!("failed to visualize day {}: {}", solution.day(), err),
+            }
+        }
+        return;
+    }
+
+    if opt.get_flag("bench") {
+        let baseline_path = opt.get_one::<PathBuf>("baseline").unwrap();
+        let mut baseline = Baseline::load(baseline_path)
+            .unwrap_or_else(|err| panic!("failed to load {}: {}", baseline_path.display(), err));
+        let mut any_regression = false;
+        for (i, solution) in solutions.into_iter().enumerate() {
+            if i != 0 {
+                println!()
+            }
+            let data = read_input(&opt, solution.day());
+            any_regression |= bench_day(solution, &data, &opt, &mut baseline);
+        }
+        if opt.get_flag("save-baseline") {
+            baseline.save(baseline_path).unwrap_or_else(|err| {
+                panic!("failed to save {}: {}", baseline_path.display(), err)
+            });
+            println!();
+            println!(
+                "{}",
+                format!("Saved {}", baseline_path.display()).bright_black()
+            );
+        }
+        if any_regression {
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let check = opt.get_flag("check");
+    let record = opt.get_flag("record");
+    let mut summary = CheckSummary::default();
+    for (i, solution) in solutions.into_iter().enumerate() {
+        if i != 0 {
+            println!()
+        }
+        let data = read_input(&opt, solution.day());
+        let answers_path = answers_path(&opt, solution.day());
+        let expected = if check || record {
+            Some(load_expected_answers(answers_path.as_ref()))
+        } else {
+            None
+        };
+        let answers = run_day(
+            solution,
+            &data,
+            expected.as_ref().filter(|_| check),
+            &mut summary,
+            &budget(&opt),
+            opt.get_flag("stats"),
+        );
+        if let (true, Some(answers), Some(mut expected), Some(path)) =
+            (record, answers, expected, answers_path)
+        {
+            for (part, answer) in Part::ALL.into_iter().zip(answers) {
+                expected.set(part, answer);
+            }
+            expected
+                .save(&path)
+                .unwrap_or_else(|err| panic!("failed to save {}: {}", path.display(), err));
+            println!(
+                "  - {}",
+                format!("recorded in {}", path.display()).bright_black()
+            );
+        }
+    }
+
+    if check {
+        println!();
+        let line = format!("Check: {}", summary);
+        if summary.failed == 0 {
+            println!("{}", line.green());
+        } else {
+            println!("{}", line.red());
+            std::process::exit(1);
+        }
+    }
 }
@@ -0,0 +1,163 @@
+use std::borrow::Cow;
+use std::fmt::Display;
+
+/// What [`normalize`] changed in an input.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Normalization {
+    pub removed_bom: bool,
+    pub crlf_line_endings: usize,
+    pub trailing_blank_lines: usize,
+}
+
+impl Normalization {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+fn plural(count: usize) -> &'static str {
+    if count == 1 {
+        ""
+    } else {
+        "s"
+    }
+}
+
+impl Display for Normalization {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut changes = Vec::new();
+        if self.removed_bom {
+            changes.push("removed the byte order mark".to_owned());
+        }
+        if 0 < self.crlf_line_endings {
+            changes.push(format!(
+                "converted {} CRLF line ending{}",
+                self.crlf_line_endings,
+                plural(self.crlf_line_endings)
+            ));
+        }
+        if 0 < self.trailing_blank_lines {
+            changes.push(format!(
+                "trimmed {} trailing blank line{}",
+                self.trailing_blank_lines,
+                plural(self.trailing_blank_lines)
+            ));
+        }
+        if changes.is_empty() {
+            write!(f, "unchanged")
+        } else {
+            write!(f, "{}", changes.join(", "))
+        }
+    }
+}
+
+/// Undoes what saving an input on another machine tends to do to it: strips a BOM, converts CRLF
+/// line endings and trims blank lines after the last line. An input that ended with a line break
+/// keeps one.
+pub fn normalize(input: &str) -> (Cow<'_, str>, Normalization) {
+    let mut normalization = Normalization::default();
+    let mut text = Cow::Borrowed(input);
+    if let Some(rest) = input.strip_prefix('\u{feff}') {
+        normalization.removed_bom = true;
+        text = Cow::Borrowed(rest);
+    }
+    normalization.crlf_line_endings = text.matches("\r\n").count();
+    if 0 < normalization.crlf_line_endings {
+        text = Cow::Owned(text.replace("\r\n", "\n"));
+    }
+
+    let content_end = match text.rfind(|c: char| !c.is_whitespace()) {
+        Some(last) => text[last..].find('\n').map_or(text.len(), |i| last + i),
+        None => 0,
+    };
+    let (keep, blank_lines) = match &text[content_end..] {
+        "" | "\n" => (text.len(), 0),
+        rest if content_end == 0 => (0, rest.lines().count()),
+        rest => (content_end + 1, rest[1..].lines().count()),
+    };
+    if 0 < blank_lines {
+        normalization.trailing_blank_lines = blank_lines;
+        text = match text {
+            Cow::Borrowed(text) => Cow::Borrowed(&text[..keep]),
+            Cow::Owned(mut text) => {
+                text.truncate(keep);
+                Cow::Owned(text)
+            }
+        };
+    }
+    (text, normalization)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::answer::Answer;
+    use crate::solution::{get, Part};
+
+    #[test]
+    fn clean_input_is_borrowed() {
+        for input in ["", "a\nb\n", "a\n\nb", "  indented\n"] {
+            let (text, normalization) = normalize(input);
+            assert!(matches!(text, Cow::Borrowed(_)));
+            assert_eq!(text, input);
+            assert!(normalization.is_empty());
+            assert_eq!(normalization.to_string(), "unchanged");
+        }
+    }
+
+    #[test]
+    fn fixes() {
+        let (text, normalization) = normalize("\u{feff}a\r\n\r\nb\r\n\r\n  \r\n\n");
+        assert_eq!(text, "a\n\nb\n");
+        assert_eq!(
+            normalization,
+            Normalization {
+                removed_bom: true,
+                crlf_line_endings: 5,
+                trailing_blank_lines: 3,
+            }
+        );
+        assert_eq!(
+            normalization.to_string(),
+            "removed the byte order mark, converted 5 CRLF line endings, \
+             trimmed 3 trailing blank lines"
+        );
+
+        assert_eq!(
+            normalize("a\r\n\n").1.to_string(),
+            "converted 1 CRLF line ending, trimmed 1 trailing blank line"
+        );
+        assert_eq!(normalize("a  \n \n").0, "a  \n");
+        assert_eq!(normalize("a\n ").0, "a\n");
+        assert_eq!(normalize("a").0, "a");
+        assert_eq!(normalize("\n\n").0, "");
+        assert_eq!(normalize("\u{feff}").0, "");
+    }
+
+    #[test]
+    fn days_parse_normalized_inputs() {
+        for (day, input, answer) in [
+            (8, "30373\n25512\n65332\n33549\n35390\n", Answer::Number(21)),
+            (
+                12,
+                "Sabqponm\nabcryxxl\naccszExk\nacctuvwj\nabdefghi\n",
+                31.into(),
+            ),
+            (
+                17,
+                ">>><<><>><<<>><>>><<<>>><<<><<<>><>><<>>\n",
+                3068.into(),
+            ),
+            (
+                24,
+                "#.######\n#>>.<^<#\n#.<..<<#\n#>v.><>#\n#<^v^^>#\n######.#\n",
+                18.into(),
+            ),
+        ] {
+            let saved_elsewhere = format!("\u{feff}{}\n\n", input.replace('\n', "\r\n"));
+            let Some(solution) = get(day) else { continue };
+            let parsed = solution.parse(&saved_elsewhere).unwrap();
+            assert_eq!(solution.run_part(Part::One, &parsed), Ok(answer));
+        }
+    }
+}
@@ -0,0 +1,277 @@
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use regex::{Captures, Regex};
+
+use crate::error::{parse_part, ParseError};
+use crate::vmatrix::VMatrix;
+
+/// A regex that is compiled the first time it is used, so it can be kept in a `static`.
+pub struct Pattern {
+    source: &'static str,
+    regex: OnceLock<Regex>,
+}
+
+impl Pattern {
+    pub const fn new(source: &'static str) -> Self {
+        Self {
+            source,
+            regex: OnceLock::new(),
+        }
+    }
+
+    pub fn regex(&self) -> &Regex {
+        self.regex
+            .get_or_init(|| Regex::new(self.source).expect("invalid pattern"))
+    }
+
+    /// The pattern should be anchored if it has to match all of `text`.
+    pub fn captures<'a>(
+        &self,
+        input: &str,
+        text: &'a str,
+        expected: &str,
+    ) -> Result<Captures<'a>, ParseError> {
+        self.regex()
+            .captures(text)
+            .ok_or_else(|| ParseError::new(input, text, expected))
+    }
+}
+
+/// An integer with an optional sign. Unlike `str::parse` for some types, it rejects anything but
+/// digits after the sign.
+pub fn integer<T: FromStr>(input: &str, text: &str, expected: &str) -> Result<T, ParseError> {
+    let digits = text.strip_prefix(['-', '+']).unwrap_or(text);
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(ParseError::new(input, text, expected));
+    }
+    parse_part(input, text, expected)
+}
+
+/// Splits `text` at the first `separator`.
+pub fn pair<'a>(
+    input: &str,
+    text: &'a str,
+    separator: &str,
+    expected: &str,
+) -> Result<(&'a str, &'a str), ParseError> {
+    text.split_once(separator)
+        .ok_or_else(|| ParseError::new(input, text, expected))
+}
+
+/// Parses each `separator` separated item of `text`, like the `", "` lists and the `" -> "`
+/// paths.
+pub fn list<'a, T>(
+    text: &'a str,
+    separator: &str,
+    item: impl FnMut(&'a str) -> Result<T, ParseError>,
+) -> Result<Vec<T>, ParseError> {
+    text.split(separator).map(item).collect()
+}
+
+pub fn integers<T: FromStr>(
+    input: &str,
+    text: &str,
+    separator: &str,
+    expected: &str,
+) -> Result<Vec<T>, ParseError> {
+    list(text, separator, |item| integer(input, item, expected))
+}
+
+/// The parts of `input` between blank lines, without their final line break. Consecutive blank
+/// lines count as one separator.
+pub fn blocks(input: &str) -> impl Iterator<Item = &str> {
+    let input = input.strip_suffix('\n').unwrap_or(input);
+    input
+        .split("\n\n")
+        .map(|block| block.trim_start_matches('\n'))
+        .filter(|block| !block.is_empty())
+}
+
+/// Splits a `key: value` line. Indentation before the key is ignored.
+pub fn key_value<'a>(input: &str, line: &'a str) -> Result<(&'a str, &'a str), ParseError> {
+    pair(input, line.trim_start(), ": ", "key: value")
+}
+
+/// The value of a `key: value` line that must have the given key.
+pub fn field<'a>(input: &str, line: &'a str, key: &str) -> Result<&'a str, ParseError> {
+    let expected = format!("{}: ...", key);
+    match key_value(input, line) {
+        Ok((found, value)) if found == key => Ok(value),
+        Ok((found, _)) => Err(ParseError::new(input, found, expected)),
+        Err(_) => Err(ParseError::new(input, line, expected)),
+    }
+}
+
+pub fn prefixed<'a>(input: &str, text: &'a str, prefix: &str) -> Result<&'a str, ParseError> {
+    text.strip_prefix(prefix)
+        .ok_or_else(|| ParseError::new(input, text, format!("{:?}", prefix)))
+}
+
+/// Each character of `text` along with the slice that holds it, for pointing errors at it.
+pub fn chars(text: &str) -> impl Iterator<Item = (char, &str)> {
+    text.char_indices()
+        .map(move |(i, c)| (c, &text[i..i + c.len_utf8()]))
+}
+
+/// Fails on the first character that is not `allowed`.
+pub fn check_chars(
+    input: &str,
+    text: &str,
+    allowed: impl Fn(char) -> bool,
+    expected: &str,
+) -> Result<(), ParseError> {
+    match chars(text).find(|(c, _)| !allowed(*c)) {
+        Some((_, at)) => Err(ParseError::new(input, at, expected)),
+        None => Ok(()),
+    }
+}
+
+/// A rectangular grid of characters with at least one of them. `cells` names the grid's cells for
+/// the errors about row lengths, and `cell` rejects a character by returning `None`.
+pub fn grid<T>(
+    input: &str,
+    text: &str,
+    cells: &str,
+    expected: &str,
+    mut cell: impl FnMut(char) -> Option<T>,
+) -> Result<VMatrix<T>, ParseError> {
+    let mut cols = None;
+    let mut values = Vec::new();
+    for line in text.lines() {
+        let row_len = line.chars().count();
+        if *cols.get_or_insert(row_len) != row_len {
+            return Err(ParseError::new(
+                input,
+                line,
+                format!("a row of {} {}", cols.unwrap(), cells),
+            ));
+        }
+        for (c, at) in chars(line) {
+            values.push(cell(c).ok_or_else(|| ParseError::new(input, at, expected))?);
+        }
+    }
+    let cols = cols.filter(|cols| 0 < *cols).ok_or_else(|| {
+        ParseError::new(input, &text[text.len()..], format!("a row of {}", cells))
+    })?;
+    Ok(VMatrix {
+        cols,
+        rows: values.len() / cols,
+        values,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn integers_with_sign() {
+        let input = "3,-14,+15,x,9 ,-";
+        assert_eq!(integer::<i32>(input, &input[2..5], "n"), Ok(-14));
+        assert_eq!(integer::<i32>(input, &input[6..9], "n"), Ok(15));
+        for (start, end, column) in [(10, 11, 11), (12, 14, 13), (15, 16, 16)] {
+            let error = integer::<i32>(input, &input[start..end], "n").unwrap_err();
+            assert_eq!((error.line, error.column), (1, column));
+        }
+        assert!(integer::<usize>(input, &input[2..5], "n").is_err());
+        assert_eq!(
+            integers::<i32>("1, -2", "1, -2", ", ", "n"),
+            Ok(vec![1, -2])
+        );
+    }
+
+    #[test]
+    fn lists_and_pairs() {
+        let input = "498,4 -> 498,6\nno pair";
+        let line = input.lines().next().unwrap();
+        let path = list(line, " -> ", |coord| {
+            let (x, y) = pair(input, coord, ",", "x,y")?;
+            Ok([integer::<u32>(input, x, "x")?, integer(input, y, "y")?])
+        });
+        assert_eq!(path, Ok(vec![[498, 4], [498, 6]]));
+        let error = pair(input, input.lines().nth(1).unwrap(), ",", "x,y").unwrap_err();
+        assert_eq!(
+            (error.line, error.column, error.text.as_str()),
+            (2, 1, "no pair")
+        );
+    }
+
+    #[test]
+    fn blocks_between_blank_lines() {
+        assert_eq!(
+            blocks("a\nb\n\nc\n\n\nd\n").collect::<Vec<_>>(),
+            ["a\nb", "c", "d"]
+        );
+        assert_eq!(blocks("").count(), 0);
+        assert_eq!(blocks("\n").count(), 0);
+    }
+
+    #[test]
+    fn key_values() {
+        let input = "Monkey 0:\n  Starting items: 79, 98\n  Test: x";
+        let mut lines = input.lines().skip(1);
+        assert_eq!(
+            field(input, lines.next().unwrap(), "Starting items"),
+            Ok("79, 98")
+        );
+        let error = field(input, lines.next().unwrap(), "Operation").unwrap_err();
+        assert_eq!(
+            (error.line, error.column, error.text.as_str()),
+            (3, 3, "Test")
+        );
+        assert!(key_value(input, &input[..9]).is_err());
+    }
+
+    #[test]
+    fn prefixes() {
+        let input = "addx -3";
+        assert_eq!(prefixed(input, input, "addx "), Ok("-3"));
+        let error = prefixed(input, input, "noop").unwrap_err();
+        assert_eq!(error.expected, "\"noop\"");
+    }
+
+    #[test]
+    fn patterns_compile_once() {
+        static PATTERN: Pattern = Pattern::new(r"^move (\d+)$");
+        let input = "move 3\nmove x";
+        let captures = PATTERN.captures(input, "move 3", "move N").unwrap();
+        assert_eq!(&captures[1], "3");
+        assert!(std::ptr::eq(PATTERN.regex(), PATTERN.regex()));
+        let error = PATTERN.captures(input, &input[7..], "move N").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 1));
+    }
+
+    #[test]
+    fn grids() {
+        let input = "12\n34\n";
+        let parsed = grid(input, input, "digits", "a digit", |c| c.to_digit(10)).unwrap();
+        assert_eq!((parsed.cols, parsed.rows), (2, 2));
+        assert_eq!(parsed.values, [1, 2, 3, 4]);
+
+        let input = "12\n3x\n";
+        let error = grid(input, input, "digits", "a digit", |c| c.to_digit(10)).unwrap_err();
+        assert_eq!(
+            (error.line, error.column, error.expected.as_str()),
+            (2, 2, "a digit")
+        );
+
+        let input = "12\n345\n";
+        let error = grid(input, input, "digits", "a digit", |c| c.to_digit(10)).unwrap_err();
+        assert_eq!(error.expected, "a row of 2 digits");
+
+        let error = grid("", "", "digits", "a digit", |c| c.to_digit(10)).unwrap_err();
+        assert_eq!(error.expected, "a row of digits");
+    }
+
+    #[test]
+    fn chars_point_at_themselves() {
+        let input = "ab\ncé!";
+        let error = check_chars(input, &input[3..], char::is_alphabetic, "a letter").unwrap_err();
+        assert_eq!((error.line, error.column, error.text.as_str()), (2, 3, "!"));
+        assert_eq!(
+            check_chars(input, &input[..2], char::is_alphabetic, "a letter"),
+            Ok(())
+        );
+    }
+}
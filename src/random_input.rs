@@ -0,0 +1,1000 @@
+use std::fmt::Write;
+use std::ops::{Range, RangeInclusive};
+
+use hashbrown::HashSet;
+use itertools::Itertools;
+
+/// SplitMix64. Not suitable for anything but making up puzzle inputs.
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    pub fn range(&mut self, range: Range<usize>) -> usize {
+        assert!(!range.is_empty(), "cannot pick from an empty range");
+        range.start + (self.next_u64() % range.len() as u64) as usize
+    }
+
+    pub fn signed_range(&mut self, range: RangeInclusive<isize>) -> isize {
+        let len = range.end().abs_diff(*range.start()) as u64 + 1;
+        range.start() + (self.next_u64() % len) as isize
+    }
+
+    pub fn chance(&mut self, probability: f64) -> bool {
+        ((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64) < probability
+    }
+
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.range(0..items.len())]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.range(0..i + 1));
+        }
+    }
+
+    fn letters(&mut self, alphabet: &[u8], len: usize) -> String {
+        (0..len).map(|_| *self.pick(alphabet) as char).collect()
+    }
+}
+
+const LOWERCASE: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
+const UPPERCASE: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+/// Makes up an input for `day` that follows the rules of the puzzle. What `size` controls depends
+/// on the day - it is roughly the number of items in the input, or the side of its grid.
+///
+/// Returns `None` for days without a generator.
+pub fn generate(day: u8, seed: u64, size: usize) -> Option<String> {
+    let rng = &mut Rng::new(seed);
+    let size = size.max(1);
+    Some(match day {
+        1 => day1(rng, size),
+        2 => day2(rng, size),
+        3 => day3(rng, size),
+        4 => day4(rng, size),
+        5 => day5(rng, size),
+        6 => day6(rng, size),
+        7 => day7(rng, size),
+        8 => day8(rng, size),
+        9 => day9(rng, size),
+        10 => day10(rng),
+        11 => day11(rng, size),
+        12 => day12(rng, size),
+        13 => day13(rng, size),
+        14 => day14(rng, size),
+        15 => day15(rng, size),
+        16 => day16(rng, size),
+        17 => day17(rng, size),
+        18 => day18(rng, size),
+        19 => day19(rng, size),
+        20 => day20(rng, size),
+        21 => day21(rng, size),
+        22 => day22(rng, size),
+        23 => day23(rng, size),
+        24 => day24(rng, size),
+        25 => day25(rng, size),
+        _ => return None,
+    })
+}
+
+fn day1(rng: &mut Rng, num_elves: usize) -> String {
+    (0..num_elves)
+        .map(|_| {
+            (0..rng.range(1..15))
+                .map(|_| format!("{}\n", rng.range(1000..60000)))
+                .collect::<String>()
+        })
+        .join("\n")
+}
+
+fn day2(rng: &mut Rng, num_rounds: usize) -> String {
+    (0..num_rounds)
+        .map(|_| {
+            format!(
+                "{} {}\n",
+                *rng.pick(b"ABC") as char,
+                *rng.pick(b"XYZ") as char
+            )
+        })
+        .collect()
+}
+
+/// `num_groups` groups of three elves. Each rucksack has exactly one item type in both
+/// compartments, and each group has exactly one item type common to all three elves.
+fn day3(rng: &mut Rng, num_groups: usize) -> String {
+    let all_item_types = [LOWERCASE, UPPERCASE].concat();
+    let mut result = String::new();
+    for _ in 0..num_groups {
+        let mut item_types = all_item_types.clone();
+        rng.shuffle(&mut item_types);
+        let badge = item_types.pop().unwrap();
+        // The first three item types are the ones each elf has in both compartments. The others go
+        // to at most two of the elves.
+        let mut elves_items = [0, 1, 2].map(|i| vec![item_types[i]]);
+        for &item_type in &item_types[3..] {
+            let left_out = rng.range(0..3);
+            for (i, elf_items) in elves_items.iter_mut().enumerate() {
+                if i != left_out && rng.chance(0.4) {
+                    elf_items.push(item_type);
+                }
+            }
+        }
+        for elf_items in elves_items {
+            let shared = elf_items[0];
+            let mut compartments = [vec![shared], vec![shared]];
+            for &item_type in elf_items[1..].iter().chain([&badge]) {
+                compartments[rng.range(0..2)].push(item_type);
+            }
+            let len = compartments[0].len().max(compartments[1].len()) + rng.range(0..8);
+            for compartment in compartments.iter_mut() {
+                while compartment.len() < len {
+                    let item_type = *rng.pick(compartment);
+                    compartment.push(item_type);
+                }
+                rng.shuffle(compartment);
+                result.extend(compartment.iter().map(|&item_type| item_type as char));
+            }
+            result.push('\n');
+        }
+    }
+    result
+}
+
+fn day4(rng: &mut Rng, num_pairs: usize) -> String {
+    let mut result = String::new();
+    for _ in 0..num_pairs {
+        let [start1, start2] = [(); 2].map(|_| rng.range(1..100));
+        let [end1, end2] = [start1, start2].map(|start| rng.range(start..100));
+        writeln!(result, "{}-{},{}-{}", start1, end1, start2, end2).unwrap();
+    }
+    result
+}
+
+/// `num_commands` moves between up to 9 stacks. No stack is ever emptied.
+fn day5(rng: &mut Rng, num_commands: usize) -> String {
+    let num_stacks = rng.range(3..10);
+    let mut stacks = (0..num_stacks)
+        .map(|_| {
+            let height = rng.range(1..9);
+            rng.letters(UPPERCASE, height).into_bytes()
+        })
+        .collect_vec();
+    // With more crates than stacks there is always a stack with a crate to spare.
+    if stacks.iter().all(|stack| stack.len() == 1) {
+        stacks[0].push(*rng.pick(UPPERCASE));
+    }
+    let mut result = String::new();
+    let height = stacks.iter().map(|stack| stack.len()).max().unwrap();
+    for level in (0..height).rev() {
+        let line = stacks
+            .iter()
+            .map(|stack| match stack.get(level) {
+                Some(&crate_letter) => format!("[{}]", crate_letter as char),
+                None => "   ".to_owned(),
+            })
+            .join(" ");
+        writeln!(result, "{}", line).unwrap();
+    }
+    let index_line = (1..=num_stacks).map(|i| format!(" {} ", i)).join(" ");
+    writeln!(result, "{}\n", index_line).unwrap();
+    for _ in 0..num_commands {
+        let movable = (0..num_stacks)
+            .filter(|&i| 1 < stacks[i].len())
+            .collect_vec();
+        let from = *rng.pick(&movable);
+        let to = (from + rng.range(1..num_stacks)) % num_stacks;
+        let amount = rng.range(1..stacks[from].len());
+        let keep = stacks[from].len() - amount;
+        let moved = stacks[from].split_off(keep);
+        stacks[to].extend(moved);
+        writeln!(result, "move {} from {} to {}", amount, from + 1, to + 1).unwrap();
+    }
+    result
+}
+
+/// Noise around a 14 letters marker. The noise uses too few letters to have a marker of its own.
+fn day6(rng: &mut Rng, size: usize) -> String {
+    let alphabet = &LOWERCASE[..rng.range(3..14)];
+    let mut marker = LOWERCASE.to_vec();
+    rng.shuffle(&mut marker);
+    marker.truncate(14);
+    let [before, after] = [rng.range(0..size), rng.range(1..size + 1)];
+    let mut result = rng.letters(alphabet, before);
+    result.extend(marker.iter().map(|&letter| letter as char));
+    result += &rng.letters(alphabet, after);
+    result.push('\n');
+    result
+}
+
+/// A terminal session exploring a filesystem with `num_files` files. The disk is full enough for
+/// part 2 to need to delete a directory, but not over its capacity.
+fn day7(rng: &mut Rng, num_files: usize) -> String {
+    struct Directory {
+        names: HashSet<String>,
+        subdirectories: Vec<(String, usize)>,
+        files: Vec<(String, usize)>,
+    }
+    let unique_name = |rng: &mut Rng, directory: &mut Directory| loop {
+        let len = rng.range(1..9);
+        let mut name = rng.letters(LOWERCASE, len);
+        if rng.chance(0.3) {
+            name = format!("{}.{}", name, rng.letters(LOWERCASE, 3));
+        }
+        if directory.names.insert(name.clone()) {
+            return name;
+        }
+    };
+    let mut directories = Vec::<Directory>::new();
+    for i in 0..num_files / 3 + 1 {
+        directories.push(Directory {
+            names: HashSet::new(),
+            subdirectories: Vec::new(),
+            files: Vec::new(),
+        });
+        if 0 < i {
+            let parent = rng.range(0..i);
+            let name = unique_name(rng, &mut directories[parent]);
+            directories[parent].subdirectories.push((name, i));
+        }
+    }
+    let mut sizes = (0..num_files)
+        .map(|_| {
+            let digits = rng.range(2..8) as u32;
+            rng.range(1..10usize.pow(digits))
+        })
+        .collect_vec();
+    let max_used = 69_000_000;
+    let total = sizes.iter().sum::<usize>();
+    if max_used < total {
+        for size in sizes.iter_mut() {
+            *size = (*size * (max_used - num_files) / total).max(1);
+        }
+    }
+    let total = sizes.iter().sum::<usize>();
+    if total <= 40_000_000 {
+        sizes.push(rng.range(40_000_001..max_used) - total);
+    }
+    for size in sizes {
+        let directory = rng.range(0..directories.len());
+        let name = unique_name(rng, &mut directories[directory]);
+        directories[directory].files.push((name, size));
+    }
+
+    fn visit(rng: &mut Rng, directories: &[Directory], idx: usize, result: &mut String) {
+        let directory = &directories[idx];
+        let mut listing = directory
+            .subdirectories
+            .iter()
+            .map(|(name, _)| format!("dir {}", name))
+            .chain(
+                directory
+                    .files
+                    .iter()
+                    .map(|(name, size)| format!("{} {}", size, name)),
+            )
+            .collect_vec();
+        rng.shuffle(&mut listing);
+        result.push_str("$ ls\n");
+        for line in listing {
+            writeln!(result, "{}", line).unwrap();
+        }
+        for (name, subdirectory) in directory.subdirectories.iter() {
+            writeln!(result, "$ cd {}", name).unwrap();
+            visit(rng, directories, *subdirectory, result);
+            result.push_str("$ cd ..\n");
+        }
+    }
+    let mut result = "$ cd /\n".to_owned();
+    visit(rng, &directories, 0, &mut result);
+    result
+}
+
+fn day8(rng: &mut Rng, side: usize) -> String {
+    (0..side)
+        .map(|_| rng.letters(b"0123456789", side) + "\n")
+        .collect()
+}
+
+fn day9(rng: &mut Rng, num_motions: usize) -> String {
+    (0..num_motions)
+        .map(|_| format!("{} {}\n", *rng.pick(b"LRUD") as char, rng.range(1..20)))
+        .collect()
+}
+
+/// The CRT always draws 240 pixels, so there is no size.
+fn day10(rng: &mut Rng) -> String {
+    let mut result = String::new();
+    let mut cycles = 0;
+    while cycles < 240 {
+        if cycles < 239 && rng.chance(0.6) {
+            writeln!(result, "addx {}", rng.signed_range(-20..=20)).unwrap();
+            cycles += 2;
+        } else {
+            result.push_str("noop\n");
+            cycles += 1;
+        }
+    }
+    result
+}
+
+/// `num_monkeys` monkeys, at least two. The divisors are primes, and with three monkeys or more
+/// one of them squares the worry level. Like in the real inputs, the worry levels of part 1 fit in
+/// a `usize`.
+fn day11(rng: &mut Rng, num_monkeys: usize) -> String {
+    struct Monkey {
+        items: Vec<usize>,
+        operation: (char, Option<usize>),
+        divisor: usize,
+        targets: [usize; 2],
+    }
+
+    fn part_1_fits(monkeys: &[Monkey]) -> bool {
+        let mut items = monkeys.iter().map(|m| m.items.clone()).collect_vec();
+        for _ in 0..20 {
+            for (i, monkey) in monkeys.iter().enumerate() {
+                for old in std::mem::take(&mut items[i]) {
+                    let operand = monkey.operation.1.unwrap_or(old);
+                    let new = match monkey.operation.0 {
+                        '+' => old.checked_add(operand),
+                        _ => old.checked_mul(operand),
+                    };
+                    let Some(new) = new else { return false };
+                    let new = new / 3;
+                    let target = monkey.targets[usize::from(new % monkey.divisor != 0)];
+                    items[target].push(new);
+                }
+            }
+        }
+        true
+    }
+
+    let num_monkeys = num_monkeys.max(2);
+    let mut primes = [2, 3, 5, 7, 11, 13, 17, 19, 23];
+    let other_monkey =
+        |rng: &mut Rng, monkey: usize| (monkey + rng.range(1..num_monkeys)) % num_monkeys;
+    let monkeys = loop {
+        rng.shuffle(&mut primes);
+        let squaring = (2 < num_monkeys).then(|| rng.range(0..num_monkeys));
+        let monkeys = (0..num_monkeys)
+            .map(|monkey| {
+                let items = (0..rng.range(1..7)).map(|_| rng.range(50..100)).collect();
+                let operation = if Some(monkey) == squaring {
+                    ('*', None)
+                } else if rng.chance(0.5) {
+                    ('+', Some(rng.range(1..9)))
+                } else {
+                    ('*', Some(rng.range(2..20)))
+                };
+                let if_true = other_monkey(rng, monkey);
+                let mut if_false = other_monkey(rng, monkey);
+                while if_false == if_true && 2 < num_monkeys {
+                    if_false = other_monkey(rng, monkey);
+                }
+                Monkey {
+                    items,
+                    operation,
+                    divisor: primes[monkey % primes.len()],
+                    targets: [if_true, if_false],
+                }
+            })
+            .collect_vec();
+        if part_1_fits(&monkeys) {
+            break monkeys;
+        }
+    };
+    monkeys
+        .iter()
+        .enumerate()
+        .map(|(i, monkey)| {
+            format!(
+                "Monkey {}:
+  Starting items: {}
+  Operation: new = old {} {}
+  Test: divisible by {}
+    If true: throw to monkey {}
+    If false: throw to monkey {}
+",
+                i,
+                monkey.items.iter().join(", "),
+                monkey.operation.0,
+                monkey
+                    .operation
+                    .1
+                    .map_or_else(|| "old".to_owned(), |operand| operand.to_string()),
+                monkey.divisor,
+                monkey.targets[0],
+                monkey.targets[1],
+            )
+        })
+        .join("\n")
+}
+
+/// A map with `side` rows and at least 26 columns. A path that climbs at most one step at a time
+/// leads from `S` to `E`.
+fn day12(rng: &mut Rng, side: usize) -> String {
+    let rows = side.max(2);
+    let cols = side.max(26);
+    let mut grid = (0..rows)
+        .map(|_| rng.letters(LOWERCASE, cols).into_bytes())
+        .collect_vec();
+    let start = [rng.range(0..rows), 0];
+    let end = [rng.range(0..rows), rng.range(25..cols)];
+    let mut moves = vec![[0, 1]; end[1] - start[1]];
+    let vertical = if start[0] < end[0] {
+        [1, 0]
+    } else {
+        [usize::MAX, 0]
+    };
+    moves.extend((0..start[0].abs_diff(end[0])).map(|_| vertical));
+    rng.shuffle(&mut moves);
+    let path_len = moves.len() + 1;
+    let mut pos = start;
+    for (i, step) in moves.into_iter().enumerate() {
+        grid[pos[0]][pos[1]] = b'a' + (25 * i / (path_len - 1)) as u8;
+        pos = [pos[0].wrapping_add(step[0]), pos[1] + step[1]];
+    }
+    grid[start[0]][start[1]] = b'S';
+    grid[end[0]][end[1]] = b'E';
+    grid.into_iter()
+        .map(|row| String::from_utf8(row).unwrap() + "\n")
+        .collect()
+}
+
+#[derive(Clone)]
+enum Packet {
+    Number(usize),
+    List(Vec<Packet>),
+}
+
+impl std::fmt::Display for Packet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Packet::Number(number) => write!(f, "{}", number),
+            Packet::List(items) => write!(f, "[{}]", items.iter().join(",")),
+        }
+    }
+}
+
+impl Packet {
+    fn random_list(rng: &mut Rng, depth: usize) -> Vec<Packet> {
+        (0..rng.range(0..5))
+            .map(|_| {
+                if 0 < depth && rng.chance(0.3) {
+                    Packet::List(Self::random_list(rng, depth - 1))
+                } else {
+                    Packet::Number(rng.range(0..11))
+                }
+            })
+            .collect()
+    }
+
+    /// Either changes a number or adds an item, so that the result never compares equal to the
+    /// original.
+    fn mutate_list(rng: &mut Rng, items: &mut Vec<Packet>) {
+        let idx = rng.range(0..items.len() + 1);
+        match items.get_mut(idx) {
+            Some(Packet::Number(number)) => *number += rng.range(1..5),
+            Some(Packet::List(inner)) => Self::mutate_list(rng, inner),
+            None => items.push(Packet::Number(rng.range(0..11))),
+        }
+    }
+}
+
+/// `num_pairs` pairs of packets. The packets of each pair never compare equal.
+fn day13(rng: &mut Rng, num_pairs: usize) -> String {
+    (0..num_pairs)
+        .map(|_| {
+            let first = Packet::random_list(rng, 4);
+            let mut second = first.clone();
+            Packet::mutate_list(rng, &mut second);
+            let mut pair = [Packet::List(first), Packet::List(second)];
+            if rng.chance(0.5) {
+                pair.swap(0, 1);
+            }
+            format!("{}\n{}\n", pair[0], pair[1])
+        })
+        .join("\n")
+}
+
+/// `num_paths` rock paths below the sand source. The rocks are deeper than they are far from the
+/// source to the sides, so in part 1 the sand spills past them before it can pile up to the source.
+fn day14(rng: &mut Rng, num_paths: usize) -> String {
+    let spread = 10 + num_paths as isize;
+    let mut result = String::new();
+    for _ in 0..num_paths {
+        let mut pos = [
+            500 + rng.signed_range(-spread..=spread),
+            rng.signed_range(spread + 1..=3 * spread),
+        ];
+        let mut path = vec![pos];
+        let mut axis = rng.range(0..2);
+        for _ in 0..rng.range(1..5) {
+            let length = rng.signed_range(1..=6);
+            pos[axis] += if rng.chance(0.5) { length } else { -length };
+            pos = [
+                pos[0].clamp(500 - spread, 500 + spread),
+                pos[1].clamp(spread + 1, 3 * spread),
+            ];
+            if path.last() != Some(&pos) {
+                path.push(pos);
+            }
+            axis = 1 - axis;
+        }
+        let line = path
+            .iter()
+            .map(|[x, y]| format!("{},{}", x, y))
+            .join(" -> ");
+        writeln!(result, "{}", line).unwrap();
+    }
+    result
+}
+
+fn day15(rng: &mut Rng, num_decoys: usize) -> String {
+    day15_with(rng, 4000000, num_decoys)
+}
+
+/// A single gap in the search area from 0 to `search_bound`, enclosed by four sensors that each
+/// cover a quadrant around it, and `num_decoys` more sensors outside the search area.
+pub(crate) fn day15_with(rng: &mut Rng, search_bound: isize, num_decoys: usize) -> String {
+    let distance = |a: [isize; 2], b: [isize; 2]| (a[0] - b[0]).abs() + (a[1] - b[1]).abs();
+    let gap = [(); 2].map(|_| rng.signed_range(0..=search_bound));
+    let mut pairs = Vec::<([isize; 2], [isize; 2])>::new();
+    for [dx, dy] in [[-1, -1], [-1, 1], [1, -1], [1, 1]] {
+        let sensor = [gap[0] + dx * search_bound, gap[1] + dy * search_bound];
+        // On the far side, so that no other sensor covers it.
+        let beacon = [
+            sensor[0] + dx * search_bound,
+            sensor[1] + dy * (search_bound - 1),
+        ];
+        pairs.push((sensor, beacon));
+    }
+    let spread = 3 * search_bound;
+    let mut attempts = 0;
+    while pairs.len() < 4 + num_decoys && attempts < 1000 * (num_decoys + 1) {
+        attempts += 1;
+        let beacon = [
+            gap[0] + rng.signed_range(-spread..=spread),
+            gap[1] + rng.signed_range(-spread..=spread),
+        ];
+        let radius = rng.signed_range(1..=search_bound / 2);
+        let dx = rng.signed_range(-radius..=radius);
+        let dy = if rng.chance(0.5) {
+            radius - dx.abs()
+        } else {
+            dx.abs() - radius
+        };
+        let sensor = [beacon[0] + dx, beacon[1] + dy];
+        let is_valid = radius < distance(sensor, gap)
+            && pairs.iter().all(|&(other_sensor, other_beacon)| {
+                distance(other_sensor, other_beacon) < distance(other_sensor, beacon)
+                    && radius < distance(sensor, other_beacon)
+            });
+        if is_valid {
+            pairs.push((sensor, beacon));
+        }
+    }
+    rng.shuffle(&mut pairs);
+    pairs
+        .into_iter()
+        .map(|(sensor, beacon)| {
+            format!(
+                "Sensor at x={}, y={}: closest beacon is at x={}, y={}\n",
+                sensor[0], sensor[1], beacon[0], beacon[1]
+            )
+        })
+        .collect()
+}
+
+/// A connected network of `num_valves` valves, about a quarter of them with a flow rate.
+fn day16(rng: &mut Rng, num_valves: usize) -> String {
+    let num_valves = num_valves.clamp(2, UPPERCASE.len() * UPPERCASE.len());
+    let mut names = UPPERCASE
+        .iter()
+        .cartesian_product(UPPERCASE)
+        .map(|(&a, &b)| String::from_utf8(vec![a, b]).unwrap())
+        .filter(|name| name != "AA")
+        .collect_vec();
+    rng.shuffle(&mut names);
+    names.insert(0, "AA".to_owned());
+    let mut tunnels = vec![Vec::<usize>::new(); num_valves];
+    let mut connect = |a: usize, b: usize| {
+        if a != b && !tunnels[a].contains(&b) {
+            tunnels[a].push(b);
+            tunnels[b].push(a);
+        }
+    };
+    for valve in 1..num_valves {
+        connect(valve, rng.range(0..valve));
+    }
+    for _ in 0..num_valves / 3 {
+        connect(rng.range(0..num_valves), rng.range(0..num_valves));
+    }
+    let mut lines = tunnels
+        .iter()
+        .enumerate()
+        .map(|(valve, tunnels)| {
+            let rate = if 0 < valve && rng.chance(0.25) {
+                rng.range(1..26)
+            } else {
+                0
+            };
+            let tunnels_text = if let [tunnel] = tunnels.as_slice() {
+                format!("tunnel leads to valve {}", names[*tunnel])
+            } else {
+                format!(
+                    "tunnels lead to valves {}",
+                    tunnels.iter().map(|&tunnel| &names[tunnel]).join(", ")
+                )
+            };
+            format!(
+                "Valve {} has flow rate={}; {}\n",
+                names[valve], rate, tunnels_text
+            )
+        })
+        .collect_vec();
+    rng.shuffle(&mut lines);
+    lines.concat()
+}
+
+fn day17(rng: &mut Rng, num_jets: usize) -> String {
+    rng.letters(b"<>", num_jets) + "\n"
+}
+
+/// `num_cubes` cubes, filling about a third of their bounding box.
+fn day18(rng: &mut Rng, num_cubes: usize) -> String {
+    let side = (num_cubes as f64 * 3.0).cbrt().ceil() as usize + 1;
+    let mut cubes = HashSet::new();
+    let mut result = String::new();
+    while cubes.len() < num_cubes {
+        let cube = [(); 3].map(|_| rng.range(1..side + 1));
+        if cubes.insert(cube) {
+            writeln!(result, "{},{},{}", cube[0], cube[1], cube[2]).unwrap();
+        }
+    }
+    result
+}
+
+fn day19(rng: &mut Rng, num_blueprints: usize) -> String {
+    (1..=num_blueprints)
+        .map(|id| {
+            format!(
+                "Blueprint {}: Each ore robot costs {} ore. Each clay robot costs {} ore. \
+                 Each obsidian robot costs {} ore and {} clay. \
+                 Each geode robot costs {} ore and {} obsidian.\n",
+                id,
+                rng.range(2..5),
+                rng.range(2..5),
+                rng.range(2..5),
+                rng.range(4..21),
+                rng.range(2..5),
+                rng.range(5..21),
+            )
+        })
+        .collect()
+}
+
+/// `len` numbers, at least two, with exactly one zero.
+fn day20(rng: &mut Rng, len: usize) -> String {
+    let len = len.max(2);
+    let zero_at = rng.range(0..len);
+    (0..len)
+        .map(|i| {
+            let number = if i == zero_at {
+                0
+            } else {
+                let number = rng.signed_range(1..=10000);
+                if rng.chance(0.5) {
+                    -number
+                } else {
+                    number
+                }
+            };
+            format!("{}\n", number)
+        })
+        .collect()
+}
+
+struct MonkeyTree<'a> {
+    rng: &'a mut Rng,
+    names: HashSet<String>,
+    lines: Vec<String>,
+}
+
+impl MonkeyTree<'_> {
+    fn new_monkey(&mut self) -> String {
+        loop {
+            let name = self.rng.letters(LOWERCASE, 4);
+            if self.names.insert(name.clone()) {
+                return name;
+            }
+        }
+    }
+
+    fn split_budget(&mut self, budget: usize) -> [usize; 2] {
+        let first = self.rng.range(0..budget);
+        [first, budget - 1 - first]
+    }
+
+    fn small_divisor(&mut self, value: usize) -> Option<usize> {
+        let divisors = (2..10).filter(|d| value.is_multiple_of(*d)).collect_vec();
+        (!divisors.is_empty()).then(|| *self.rng.pick(&divisors))
+    }
+
+    /// A monkey that yells `value`, using `budget` operations. Every value along the way is
+    /// positive, and every division is exact.
+    fn yelling(&mut self, value: usize, budget: usize) -> String {
+        let name = self.new_monkey();
+        if budget == 0 {
+            self.lines.push(format!("{}: {}", name, value));
+            return name;
+        }
+        let [left_budget, right_budget] = self.split_budget(budget);
+        let divisor = self.small_divisor(value);
+        let (left, op, right) = match self.rng.range(0..4) {
+            0 if 2 <= value => {
+                let left = self.rng.range(1..value);
+                (left, '+', value - left)
+            }
+            1 => {
+                let right = self.rng.range(1..1000);
+                (value + right, '-', right)
+            }
+            2 if divisor.is_some() => {
+                let right = divisor.unwrap();
+                (value / right, '*', right)
+            }
+            _ if value < 100_000_000_000 => {
+                let right = self.rng.range(2..10);
+                (value * right, '/', right)
+            }
+            _ => {
+                let right = self.rng.range(1..value);
+                (value - right, '+', right)
+            }
+        };
+        let left = self.yelling(left, left_budget);
+        let right = self.yelling(right, right_budget);
+        self.lines
+            .push(format!("{}: {} {} {}", name, left, op, right));
+        name
+    }
+
+    /// Like [`Self::yelling`], but with `humn` somewhere below. Also returns what `humn` needs to
+    /// yell. There are no divisions on the path to `humn`, so any number it yells in part 1 works.
+    fn yelling_with_human(&mut self, value: usize, budget: usize) -> (String, usize) {
+        if budget == 0 {
+            return ("humn".to_owned(), value);
+        }
+        let name = self.new_monkey();
+        let [human_budget, other_budget] = self.split_budget(budget);
+        let divisor = self.small_divisor(value);
+        let (human_value, other_value, op, human_on_left) = match self.rng.range(0..4) {
+            0 if 2 <= value => {
+                let other = self.rng.range(1..value);
+                (value - other, other, '+', self.rng.chance(0.5))
+            }
+            1 => {
+                let other = self.rng.range(1..1000);
+                (value + other, other, '-', true)
+            }
+            2 if divisor.is_some() => {
+                let other = divisor.unwrap();
+                (value / other, other, '*', self.rng.chance(0.5))
+            }
+            _ => {
+                let human = self.rng.range(1..1000);
+                (human, value + human, '-', false)
+            }
+        };
+        let (human, humn_value) = self.yelling_with_human(human_value, human_budget);
+        let other = self.yelling(other_value, other_budget);
+        let line = if human_on_left {
+            format!("{}: {} {} {}", name, human, op, other)
+        } else {
+            format!("{}: {} {} {}", name, other, op, human)
+        };
+        self.lines.push(line);
+        (name, humn_value)
+    }
+}
+
+/// About `num_operations` operations. Part 2's answer is a positive integer.
+fn day21(rng: &mut Rng, num_operations: usize) -> String {
+    let humn_in_part_1 = rng.range(1..10000);
+    let root_value = rng.range(1..10_000);
+    let mut tree = MonkeyTree {
+        rng,
+        names: ["root", "humn"].map(str::to_owned).into_iter().collect(),
+        lines: vec![format!("humn: {}", humn_in_part_1)],
+    };
+    let human_budget = tree.rng.range(0..num_operations);
+    let (human, _) = tree.yelling_with_human(root_value, human_budget);
+    let other = tree.yelling(root_value, num_operations - 1 - human_budget);
+    let root = if tree.rng.chance(0.5) {
+        format!("root: {} + {}", human, other)
+    } else {
+        format!("root: {} + {}", other, human)
+    };
+    let mut lines = tree.lines;
+    lines.push(root);
+    rng.shuffle(&mut lines);
+    lines.into_iter().map(|line| line + "\n").collect()
+}
+
+const CUBE_NETS: [&[&str]; 11] = [
+    &["#   ", "####", "#   "],
+    &["#   ", "####", " #  "],
+    &["#   ", "####", "  # "],
+    &["#   ", "####", "   #"],
+    &[" #  ", "####", " #  "],
+    &[" #  ", "####", "  # "],
+    &["##  ", " ###", " #  "],
+    &["##  ", " ###", "  # "],
+    &["##  ", " ###", "   #"],
+    &["##  ", " ## ", "  ##"],
+    &["###  ", "  ###"],
+];
+
+/// A random cube net, rotated and mirrored, with faces of `face_side` tiles.
+fn day22(rng: &mut Rng, face_side: usize) -> String {
+    let mut faces = rng
+        .pick(&CUBE_NETS)
+        .iter()
+        .map(|row| row.bytes().map(|c| c == b'#').collect_vec())
+        .collect_vec();
+    for _ in 0..rng.range(0..4) {
+        // Rotate a quarter turn: the first column becomes the first row, reversed.
+        faces = (0..faces[0].len())
+            .map(|c| faces.iter().rev().map(|row| row[c]).collect())
+            .collect();
+    }
+    if rng.chance(0.5) {
+        faces.iter_mut().for_each(|row| row.reverse());
+    }
+    let mut result = String::new();
+    for row in faces.iter() {
+        let offset = row.iter().position(|&is_face| is_face).unwrap();
+        let width = row.iter().filter(|&&is_face| is_face).count();
+        for _ in 0..face_side {
+            result += &" ".repeat(offset * face_side);
+            for _ in 0..width * face_side {
+                result.push(if rng.chance(0.15) { '#' } else { '.' });
+            }
+            result.push('\n');
+        }
+    }
+    // The first tile of the first row is where the path starts.
+    let first_tile = result.find(['.', '#']).unwrap();
+    result.replace_range(first_tile..first_tile + 1, ".");
+    result.push('\n');
+    write!(result, "{}", rng.range(1..2 * face_side + 1)).unwrap();
+    for _ in 0..4 * face_side {
+        let turn = *rng.pick(b"LR") as char;
+        write!(result, "{}{}", turn, rng.range(1..2 * face_side + 1)).unwrap();
+    }
+    result.push('\n');
+    result
+}
+
+fn day23(rng: &mut Rng, side: usize) -> String {
+    let mut rows = (0..side)
+        .map(|_| {
+            (0..side)
+                .map(|_| if rng.chance(0.5) { '#' } else { '.' })
+                .collect::<String>()
+        })
+        .collect_vec();
+    if !rows.iter().any(|row| row.contains('#')) {
+        rows[0].replace_range(0..1, "#");
+    }
+    rows.into_iter().map(|row| row + "\n").collect()
+}
+
+/// A valley `width` tiles wide and a third as high, a fifth of it with blizzards. There are no
+/// vertical blizzards in the columns of the entrance and the exit.
+fn day24(rng: &mut Rng, width: usize) -> String {
+    let width = width.max(2);
+    let height = (width / 3).max(1);
+    let mut result = format!("#.{}\n", "#".repeat(width));
+    for _ in 0..height {
+        result.push('#');
+        for x in 0..width {
+            let winds: &[u8] = if x == 0 || x == width - 1 {
+                b"<>"
+            } else {
+                b"<>^v"
+            };
+            result.push(if rng.chance(0.2) {
+                *rng.pick(winds) as char
+            } else {
+                '.'
+            });
+        }
+        result.push_str("#\n");
+    }
+    result += &format!("{}.#\n", "#".repeat(width));
+    result
+}
+
+fn day25(rng: &mut Rng, num_numbers: usize) -> String {
+    (0..num_numbers)
+        .map(|_| {
+            let first_digit = *rng.pick(b"12") as char;
+            let len = rng.range(0..20);
+            format!("{}{}\n", first_digit, rng.letters(b"=-012", len))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::budget::Budget;
+    use crate::runner::{run_solution, RunError};
+    use crate::solution::SOLUTIONS;
+
+    #[test]
+    fn same_seed_same_input() {
+        for day in 1..=25 {
+            assert_eq!(generate(day, 7, 10), generate(day, 7, 10));
+        }
+        assert_ne!(generate(16, 7, 10), generate(16, 8, 10));
+        assert_eq!(generate(26, 7, 10), None);
+    }
+
+    #[test]
+    fn generated_inputs_parse() {
+        for solution in SOLUTIONS {
+            for seed in 0..20 {
+                for size in [1, 2, 10] {
+                    let input = generate(solution.day(), seed, size).unwrap();
+                    if let Err(err) = solution.parse(&input) {
+                        panic!(
+                            "day {} seed {} size {}: {}\n{}",
+                            solution.day(),
+                            seed,
+                            size,
+                            err,
+                            input
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn small_inputs_do_not_panic() {
+        // Day 15 always searches the full area. Day 19 takes minutes even on small inputs, so its
+        // search gets a budget.
+        let budget = Budget {
+            max_nodes: Some(100_000),
+            ..Budget::default()
+        };
+        for &solution in SOLUTIONS.iter().filter(|solution| solution.day() != 15) {
+            let day = solution.day();
+            for seed in 0..5 {
+                let input = generate(day, seed, 6).unwrap();
+                let day_run = run_solution(solution, &input, &budget);
+                for part_run in day_run.parts.unwrap() {
+                    if let Err(RunError::Panic(message)) = part_run.answer {
+                        panic!("day {} seed {}: {}\n{}", day, seed, message, input);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,317 @@
+use std::any::Any;
+use std::fmt::Display;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::mpsc;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+
+use crate::answer::Answer;
+use crate::budget::Budget;
+use crate::error::{ParseError, SolveError};
+use crate::solution::{Part, Solution};
+use crate::stats::{self, SearchStats};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RunError {
+    Parse(ParseError),
+    Solve(SolveError),
+    Panic(String),
+}
+
+impl RunError {
+    fn to_json(&self) -> Value {
+        let kind = match self {
+            RunError::Parse(_) => "parse",
+            RunError::Solve(_) => "solve",
+            RunError::Panic(_) => "panic",
+        };
+        json!({ "kind": kind, "message": self.to_string() })
+    }
+}
+
+impl Display for RunError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RunError::Parse(err) => write!(f, "{}", err),
+            RunError::Solve(err) => write!(f, "{}", err),
+            RunError::Panic(message) => write!(f, "panicked: {}", message),
+        }
+    }
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic payload".to_owned()
+    }
+}
+
+fn run_catching<T>(f: impl FnOnce() -> T) -> Result<T, RunError> {
+    catch_unwind(AssertUnwindSafe(f)).map_err(|payload| RunError::Panic(panic_message(payload)))
+}
+
+#[derive(Debug, Clone)]
+pub struct PartRun {
+    pub part: Part,
+    pub answer: Result<Answer, RunError>,
+    pub duration: Duration,
+    /// False if the budget ran out and the answer is only the best found so far.
+    pub is_final: bool,
+    /// Empty if the part panicked.
+    pub stats: SearchStats,
+}
+
+pub struct DayRun {
+    pub solution: &'static dyn Solution,
+    pub generator_duration: Duration,
+    pub parts: Result<Vec<PartRun>, RunError>,
+}
+
+fn nanos(duration: Duration) -> Value {
+    json!(duration.as_nanos() as u64)
+}
+
+impl PartRun {
+    fn to_json(&self) -> Value {
+        let (answer, error) = match &self.answer {
+            Ok(answer) => (
+                json!({ "type": answer.type_name(), "value": answer.to_string() }),
+                Value::Null,
+            ),
+            Err(err) => (Value::Null, err.to_json()),
+        };
+        json!({
+            "part": self.part.name(),
+            "answer": answer,
+            "error": error,
+            "duration_ns": nanos(self.duration),
+            "final": self.is_final,
+            "stats": self.stats.to_json(),
+        })
+    }
+}
+
+impl DayRun {
+    pub fn to_json(&self) -> Value {
+        let (parts, error) = match &self.parts {
+            Ok(part_runs) => (
+                part_runs.iter().map(PartRun::to_json).collect(),
+                Value::Null,
+            ),
+            Err(err) => (Vec::new(), err.to_json()),
+        };
+        json!({
+            "day": self.solution.day(),
+            "name": self.solution.name(),
+            "generator_duration_ns": nanos(self.generator_duration),
+            "error": error,
+            "parts": parts,
+        })
+    }
+
+    pub fn total_duration(&self) -> Duration {
+        self.generator_duration
+            + self
+                .parts
+                .iter()
+                .flatten()
+                .map(|part_run| part_run.duration)
+                .sum::<Duration>()
+    }
+}
+
+/// Panics inside the solution are caught and reported as [`RunError::Panic`].
+pub fn run_solution(solution: &'static dyn Solution, data: &str, budget: &Budget) -> DayRun {
+    let start = Instant::now();
+    let input =
+        run_catching(|| solution.parse(data)).and_then(|input| input.map_err(RunError::Parse));
+    let generator_duration = start.elapsed();
+    DayRun {
+        solution,
+        generator_duration,
+        parts: input.map(|input| {
+            Part::ALL
+                .into_iter()
+                .map(|part| {
+                    let start = Instant::now();
+                    let result = run_catching(|| {
+                        stats::collect(|| solution.run_part_within(part, &input, budget))
+                    });
+                    let duration = start.elapsed();
+                    let (is_final, answer, stats) = match result {
+                        Ok((Ok(outcome), stats)) => {
+                            (outcome.is_final(), Ok(outcome.value()), stats)
+                        }
+                        Ok((Err(err), stats)) => (true, Err(RunError::Solve(err)), stats),
+                        Err(err) => (true, Err(err), SearchStats::default()),
+                    };
+                    PartRun {
+                        part,
+                        answer,
+                        duration,
+                        is_final,
+                        stats,
+                    }
+                })
+                .collect()
+        }),
+    }
+}
+
+/// Runs each solution on its input using `num_threads` worker threads, and returns the results in
+/// the order of the jobs.
+pub fn run_parallel(
+    jobs: Vec<(&'static dyn Solution, String)>,
+    num_threads: usize,
+    budget: &Budget,
+) -> Vec<DayRun> {
+    let num_jobs = jobs.len();
+    let jobs = Mutex::new(jobs.into_iter().enumerate());
+    let (sender, receiver) = mpsc::channel();
+    std::thread::scope(|scope| {
+        for _ in 0..num_threads.clamp(1, num_jobs.max(1)) {
+            let sender = sender.clone();
+            let jobs = &jobs;
+            scope.spawn(move || loop {
+                let Some((i, (solution, data))) = jobs.lock().unwrap().next() else {
+                    break;
+                };
+                sender
+                    .send((i, run_solution(solution, &data, budget)))
+                    .unwrap();
+            });
+        }
+    });
+    drop(sender);
+    let mut result = receiver.into_iter().collect::<Vec<_>>();
+    result.sort_by_key(|(i, _)| *i);
+    result.into_iter().map(|(_, day_run)| day_run).collect()
+}
+
+// The tests run a mix of days.
+#[cfg(all(test, feature = "all"))]
+mod tests {
+    use super::*;
+    use crate::solution::get;
+
+    #[test]
+    fn parallel_results_keep_jobs_order() {
+        let jobs = vec![
+            (get(6).unwrap(), "mjqjpqmgbljsphdztnvjfqwrcgsmlb".to_owned()),
+            (get(1).unwrap(), "1\n2\n\n4\n".to_owned()),
+            (get(4).unwrap(), "2-4,6-8\n".to_owned()),
+            (get(3).unwrap(), "not a rucksack\n".to_owned()),
+        ];
+        let results = run_parallel(jobs, 3, &Budget::unlimited());
+        assert_eq!(
+            results
+                .iter()
+                .map(|day_run| day_run.solution.day())
+                .collect::<Vec<_>>(),
+            [6, 1, 4, 3]
+        );
+        let answers = |day_run: &DayRun| {
+            day_run
+                .parts
+                .as_ref()
+                .map(|parts| {
+                    parts
+                        .iter()
+                        .map(|p| p.answer.clone().unwrap())
+                        .collect::<Vec<_>>()
+                })
+                .ok()
+        };
+        assert_eq!(answers(&results[0]), Some(vec![7.into(), 19.into()]));
+        assert_eq!(answers(&results[1]), Some(vec![4.into(), 7.into()]));
+        assert_eq!(answers(&results[3]), None);
+    }
+
+    #[test]
+    fn stats_per_part() {
+        let day12 = "Sabqponm\nabcryxxl\naccszExk\nacctuvwj\nabdefghi\n".to_owned();
+        let jobs = vec![
+            (get(12).unwrap(), day12.clone()),
+            (get(1).unwrap(), "1\n2\n\n4\n".to_owned()),
+            (get(12).unwrap(), day12),
+        ];
+        let results = run_parallel(jobs, 2, &Budget::unlimited());
+        let stats = |day_run: &DayRun| {
+            let parts = day_run.parts.as_ref().unwrap();
+            [parts[0].stats, parts[1].stats]
+        };
+        assert_eq!(stats(&results[0]), stats(&results[2]));
+        assert!(stats(&results[0]).iter().all(|s| 0 < s.nodes_expanded));
+        assert!(stats(&results[1]).iter().all(SearchStats::is_empty));
+    }
+
+    #[test]
+    fn panics_are_caught() {
+        // Day 6 assumes the signal is longer than the marker.
+        let day_run = run_solution(get(6).unwrap(), "abc", &Budget::unlimited());
+        let parts = day_run.parts.unwrap();
+        assert!(matches!(parts[0].answer, Err(RunError::Panic(_))));
+    }
+
+    #[test]
+    fn unsolvable_input_is_an_error() {
+        let day_run = run_solution(get(20).unwrap(), "1\n2\n", &Budget::unlimited());
+        let parts = day_run.parts.unwrap();
+        assert!(matches!(
+            parts[0].answer,
+            Err(RunError::Solve(SolveError::MissingStart(_)))
+        ));
+        assert_eq!(parts[1].to_json()["error"]["kind"], "solve");
+    }
+
+    #[test]
+    fn json_output() {
+        let day_run = run_solution(
+            get(5).unwrap(),
+            "[A]\n 1\n\nmove 1 from 1 to 1\n",
+            &Budget::unlimited(),
+        );
+        let json: Value = serde_json::from_str(&day_run.to_json().to_string()).unwrap();
+        assert_eq!(json["name"], "day5");
+        assert_eq!(json["error"], Value::Null);
+        let parts = json["parts"].as_array().unwrap();
+        assert_eq!(parts[0]["answer"], json!({ "type": "text", "value": "A" }));
+        assert!(parts[1]["duration_ns"].is_u64());
+        assert_eq!(parts[1]["stats"], SearchStats::default().to_json());
+
+        let day_run = run_solution(get(1).unwrap(), "1\nx\n", &Budget::unlimited());
+        assert_eq!(day_run.to_json()["error"]["kind"], "parse");
+    }
+
+    #[test]
+    fn budget_marks_best_so_far() {
+        let day16 = "\
+Valve AA has flow rate=0; tunnels lead to valves BB, CC
+Valve BB has flow rate=13; tunnels lead to valves AA, CC
+Valve CC has flow rate=2; tunnels lead to valves AA, BB
+";
+        let budget = Budget {
+            max_nodes: Some(1),
+            ..Budget::default()
+        };
+        for (day, data) in [(16, day16), (1, "1\n2\n\n4\n")] {
+            let parts = run_solution(get(day).unwrap(), data, &budget)
+                .parts
+                .unwrap();
+            assert!(parts
+                .iter()
+                .all(|part_run| part_run.is_final == (day != 16)));
+            assert_eq!(parts[0].to_json()["final"], day != 16);
+        }
+        let parts = run_solution(get(16).unwrap(), day16, &Budget::unlimited())
+            .parts
+            .unwrap();
+        assert!(parts.iter().all(|part_run| part_run.is_final));
+        assert_eq!(parts[0].answer, Ok(Answer::Number(28 * 13 + 26 * 2)));
+    }
+}
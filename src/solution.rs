@@ -0,0 +1,287 @@
+use std::any::Any;
+
+use crate::answer::Answer;
+use crate::budget::{Budget, Outcome};
+use crate::error::{ParseError, SolveError};
+use crate::normalize::normalize;
+
+pub type ParsedInput = Box<dyn Any + Send>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    pub const ALL: [Part; 2] = [Part::One, Part::Two];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Part::One => "part_1",
+            Part::Two => "part_2",
+        }
+    }
+}
+
+pub trait Solution: Sync {
+    fn day(&self) -> u8;
+
+    fn name(&self) -> &'static str;
+
+    /// The input is normalized first, see [`normalize`].
+    fn parse(&self, input: &str) -> Result<ParsedInput, ParseError>;
+
+    /// `input` must be the result of this solution's [`Solution::parse`].
+    fn part_1(&self, input: &ParsedInput) -> Result<Answer, SolveError>;
+
+    /// `input` must be the result of this solution's [`Solution::parse`].
+    fn part_2(&self, input: &ParsedInput) -> Result<Answer, SolveError>;
+
+    fn run_part(&self, part: Part, input: &ParsedInput) -> Result<Answer, SolveError> {
+        match part {
+            Part::One => self.part_1(input),
+            Part::Two => self.part_2(input),
+        }
+    }
+
+    /// Only some searches can stop early. The others ignore the budget and are always final.
+    fn run_part_within(
+        &self,
+        part: Part,
+        input: &ParsedInput,
+        budget: &Budget,
+    ) -> Result<Outcome<Answer>, SolveError> {
+        let _ = budget;
+        self.run_part(part, input).map(Outcome::Final)
+    }
+}
+
+pub struct DaySolution<T> {
+    pub day: u8,
+    pub name: &'static str,
+    pub generator: fn(&str) -> Result<T, ParseError>,
+    pub part_1: fn(&T) -> Result<Answer, SolveError>,
+    pub part_2: fn(&T) -> Result<Answer, SolveError>,
+    pub part_1_within: Option<BudgetedPart<T>>,
+    pub part_2_within: Option<BudgetedPart<T>>,
+}
+
+pub type BudgetedPart<T> = fn(&T, &Budget) -> Result<Outcome<Answer>, SolveError>;
+
+impl<T: Any + Send> DaySolution<T> {
+    fn downcast<'a>(&self, input: &'a ParsedInput) -> &'a T {
+        input
+            .downcast_ref()
+            .unwrap_or_else(|| panic!("input was not parsed by {}", self.name))
+    }
+}
+
+impl<T: Any + Send> Solution for DaySolution<T> {
+    fn day(&self) -> u8 {
+        self.day
+    }
+
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn parse(&self, input: &str) -> Result<ParsedInput, ParseError> {
+        let (input, _) = normalize(input);
+        Ok(Box::new((self.generator)(&input)?))
+    }
+
+    fn part_1(&self, input: &ParsedInput) -> Result<Answer, SolveError> {
+        (self.part_1)(self.downcast(input))
+    }
+
+    fn part_2(&self, input: &ParsedInput) -> Result<Answer, SolveError> {
+        (self.part_2)(self.downcast(input))
+    }
+
+    fn run_part_within(
+        &self,
+        part: Part,
+        input: &ParsedInput,
+        budget: &Budget,
+    ) -> Result<Outcome<Answer>, SolveError> {
+        let within = match part {
+            Part::One => self.part_1_within,
+            Part::Two => self.part_2_within,
+        };
+        match within {
+            Some(within) if !budget.is_unlimited() => within(self.downcast(input), budget),
+            _ => self.run_part(part, input).map(Outcome::Final),
+        }
+    }
+}
+
+/// What the part functions of a day may return: the answer, or a result with the answer.
+pub trait IntoAnswer {
+    fn into_answer(self) -> Result<Answer, SolveError>;
+}
+
+impl<T: Into<Answer>> IntoAnswer for T {
+    fn into_answer(self) -> Result<Answer, SolveError> {
+        Ok(self.into())
+    }
+}
+
+impl<T: Into<Answer>> IntoAnswer for Result<T, SolveError> {
+    fn into_answer(self) -> Result<Answer, SolveError> {
+        self.map(Into::into)
+    }
+}
+
+/// Same as [`IntoAnswer`], for the parts that take a [`Budget`].
+pub trait IntoOutcome {
+    fn into_outcome(self) -> Result<Outcome<Answer>, SolveError>;
+}
+
+impl<T: Into<Answer>> IntoOutcome for Outcome<T> {
+    fn into_outcome(self) -> Result<Outcome<Answer>, SolveError> {
+        Ok(self.map(Into::into))
+    }
+}
+
+impl<T: Into<Answer>> IntoOutcome for Result<Outcome<T>, SolveError> {
+    fn into_outcome(self) -> Result<Outcome<Answer>, SolveError> {
+        self.map(|outcome| outcome.map(Into::into))
+    }
+}
+
+// Unused when building with no day features.
+#[allow(unused_macros)]
+macro_rules! day_solution {
+    ($day:literal => $module:ident) => {
+        &DaySolution {
+            day: $day,
+            name: stringify!($module),
+            generator: crate::$module::generator,
+            part_1: |input| crate::$module::part_1(input).into_answer(),
+            part_2: |input| crate::$module::part_2(input).into_answer(),
+            part_1_within: None,
+            part_2_within: None,
+        }
+    };
+    // Days whose budgeted parts take params run them with the default ones.
+    ($day:literal => $module:ident, budgeted $(with $params:ident)?) => {
+        &DaySolution {
+            day: $day,
+            name: stringify!($module),
+            generator: crate::$module::generator,
+            part_1: |input| crate::$module::part_1(input).into_answer(),
+            part_2: |input| crate::$module::part_2(input).into_answer(),
+            part_1_within: Some(|input, budget| {
+                crate::$module::part_1_within(
+                    input,
+                    $(&crate::$module::$params::default(),)?
+                    budget,
+                )
+                .into_outcome()
+            }),
+            part_2_within: Some(|input, budget| {
+                crate::$module::part_2_within(
+                    input,
+                    $(&crate::$module::$params::default(),)?
+                    budget,
+                )
+                .into_outcome()
+            }),
+        }
+    };
+}
+
+/// All the implemented days, sorted by day number.
+pub static SOLUTIONS: &[&dyn Solution] = &[
+    #[cfg(feature = "day1")]
+    day_solution!(1 => day1),
+    #[cfg(feature = "day2")]
+    day_solution!(2 => day2),
+    #[cfg(feature = "day3")]
+    day_solution!(3 => day3),
+    #[cfg(feature = "day4")]
+    day_solution!(4 => day4),
+    #[cfg(feature = "day5")]
+    day_solution!(5 => day5),
+    #[cfg(feature = "day6")]
+    day_solution!(6 => day6),
+    #[cfg(feature = "day7")]
+    day_solution!(7 => day7),
+    #[cfg(feature = "day8")]
+    day_solution!(8 => day8),
+    #[cfg(feature = "day9")]
+    day_solution!(9 => day9),
+    #[cfg(feature = "day10")]
+    day_solution!(10 => day10),
+    #[cfg(feature = "day11")]
+    day_solution!(11 => day11),
+    #[cfg(feature = "day12")]
+    day_solution!(12 => day12),
+    #[cfg(feature = "day13")]
+    day_solution!(13 => day13),
+    #[cfg(feature = "day14")]
+    day_solution!(14 => day14),
+    #[cfg(feature = "day15")]
+    day_solution!(15 => day15),
+    #[cfg(feature = "day16")]
+    day_solution!(16 => day16, budgeted with Params),
+    #[cfg(feature = "day17")]
+    day_solution!(17 => day17),
+    #[cfg(feature = "day18")]
+    day_solution!(18 => day18),
+    #[cfg(feature = "day19")]
+    day_solution!(19 => day19, budgeted),
+    #[cfg(feature = "day20")]
+    day_solution!(20 => day20),
+    #[cfg(feature = "day21")]
+    day_solution!(21 => day21),
+    #[cfg(feature = "day22")]
+    day_solution!(22 => day22),
+    #[cfg(feature = "day23")]
+    day_solution!(23 => day23),
+    #[cfg(feature = "day24")]
+    day_solution!(24 => day24),
+    #[cfg(feature = "day25")]
+    day_solution!(25 => day25),
+];
+
+pub fn get(day: u8) -> Option<&'static dyn Solution> {
+    SOLUTIONS
+        .iter()
+        .copied()
+        .find(|solution| solution.day() == day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registry_is_sorted_by_day() {
+        assert!(SOLUTIONS
+            .windows(2)
+            .all(|pair| pair[0].day() < pair[1].day()));
+        for solution in SOLUTIONS {
+            assert_eq!(solution.name(), format!("day{}", solution.day()));
+        }
+        #[cfg(feature = "all")]
+        assert_eq!(SOLUTIONS.len(), 25);
+    }
+
+    #[test]
+    #[cfg(feature = "day1")]
+    fn run_through_trait() {
+        let solution = get(1).unwrap();
+        let input = solution.parse("1000\n2000\n\n4000\n").unwrap();
+        assert_eq!(
+            solution.run_part(Part::One, &input),
+            Ok(Answer::Number(4000))
+        );
+        assert_eq!(
+            solution.run_part(Part::Two, &input),
+            Ok(Answer::Number(7000))
+        );
+        assert!(solution.parse("1000\nfoo\n").is_err());
+    }
+}
@@ -0,0 +1,177 @@
+use std::cell::Cell;
+use std::fmt::Display;
+
+use serde_json::Value;
+
+/// What a search did while solving a part. The searches bump the counters of their thread, and
+/// [`collect`] gathers them.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SearchStats {
+    pub nodes_expanded: u64,
+    pub pruned: u64,
+    pub max_depth: u64,
+    pub memo_hits: u64,
+    pub peak_queue: u64,
+}
+
+impl SearchStats {
+    fn fields(&self) -> [(&'static str, u64); 5] {
+        [
+            ("nodes_expanded", self.nodes_expanded),
+            ("pruned", self.pruned),
+            ("max_depth", self.max_depth),
+            ("memo_hits", self.memo_hits),
+            ("peak_queue", self.peak_queue),
+        ]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn to_json(&self) -> Value {
+        self.fields()
+            .into_iter()
+            .map(|(name, value)| (name.to_owned(), value.into()))
+            .collect::<serde_json::Map<_, _>>()
+            .into()
+    }
+}
+
+/// Only the counters that were used.
+impl Display for SearchStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut first = true;
+        for (name, value) in self.fields() {
+            if value == 0 {
+                continue;
+            }
+            if !first {
+                f.write_str(", ")?;
+            }
+            first = false;
+            write!(f, "{} {}", name.replace('_', " "), value)?;
+        }
+        Ok(())
+    }
+}
+
+thread_local! {
+    static STATS: Cell<SearchStats> = const {
+        Cell::new(SearchStats {
+            nodes_expanded: 0,
+            pruned: 0,
+            max_depth: 0,
+            memo_hits: 0,
+            peak_queue: 0,
+        })
+    };
+    static DEPTH: Cell<u64> = const { Cell::new(0) };
+}
+
+fn update(f: impl FnOnce(&mut SearchStats)) {
+    STATS.with(|cell| {
+        let mut stats = cell.get();
+        f(&mut stats);
+        cell.set(stats);
+    });
+}
+
+pub fn node_expanded() {
+    update(|stats| stats.nodes_expanded += 1);
+}
+
+pub fn pruned() {
+    update(|stats| stats.pruned += 1);
+}
+
+pub fn memo_hit() {
+    update(|stats| stats.memo_hits += 1);
+}
+
+pub fn queue_size(len: usize) {
+    update(|stats| stats.peak_queue = stats.peak_queue.max(len as u64));
+}
+
+/// Counts one level of recursion until dropped.
+#[must_use]
+pub struct DepthGuard(());
+
+pub fn descend() -> DepthGuard {
+    let depth = DEPTH.with(|depth| {
+        depth.set(depth.get() + 1);
+        depth.get()
+    });
+    update(|stats| stats.max_depth = stats.max_depth.max(depth));
+    DepthGuard(())
+}
+
+impl Drop for DepthGuard {
+    fn drop(&mut self) {
+        DEPTH.with(|depth| depth.set(depth.get() - 1));
+    }
+}
+
+/// Runs `f` with fresh counters and returns what it counted.
+pub fn collect<T>(f: impl FnOnce() -> T) -> (T, SearchStats) {
+    STATS.with(|cell| cell.take());
+    DEPTH.with(|depth| depth.set(0));
+    let result = f();
+    (result, STATS.with(|cell| cell.take()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recurse(depth: usize) {
+        let _depth = descend();
+        node_expanded();
+        if depth == 0 {
+            pruned();
+        } else {
+            recurse(depth - 1);
+            recurse(depth - 1);
+        }
+    }
+
+    #[test]
+    fn counts_per_run() {
+        let ((), stats) = collect(|| recurse(3));
+        assert_eq!(
+            stats,
+            SearchStats {
+                nodes_expanded: 15,
+                pruned: 8,
+                max_depth: 4,
+                ..SearchStats::default()
+            }
+        );
+        assert_eq!(
+            stats.to_string(),
+            "nodes expanded 15, pruned 8, max depth 4"
+        );
+
+        let ((), stats) = collect(|| {
+            queue_size(3);
+            queue_size(1);
+            memo_hit();
+        });
+        assert_eq!(stats.peak_queue, 3);
+        assert_eq!(stats.memo_hits, 1);
+        assert_eq!(stats.nodes_expanded, 0);
+    }
+
+    #[test]
+    #[cfg(feature = "day12")]
+    fn bfs_is_counted() {
+        let solution = crate::solution::get(12).unwrap();
+        let input = solution
+            .parse("Sabqponm\nabcryxxl\naccszExk\nacctuvwj\nabdefghi\n")
+            .unwrap();
+        let (_, stats) = collect(|| solution.run_part(crate::solution::Part::One, &input));
+        assert!(0 < stats.nodes_expanded && stats.nodes_expanded <= 40);
+        assert!(0 < stats.peak_queue);
+        assert!(0 < stats.memo_hits);
+    }
+}
@@ -0,0 +1,420 @@
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::error::ParseError;
+use crate::normalize::normalize;
+use crate::solution::Part;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    pub const BLACK: Rgb = Rgb(0, 0, 0);
+    pub const WHITE: Rgb = Rgb(255, 255, 255);
+    pub const GRAY: Rgb = Rgb(128, 128, 128);
+    pub const DARK_GRAY: Rgb = Rgb(64, 64, 64);
+    pub const RED: Rgb = Rgb(220, 40, 40);
+    pub const GREEN: Rgb = Rgb(40, 180, 60);
+    pub const BLUE: Rgb = Rgb(50, 90, 220);
+    pub const YELLOW: Rgb = Rgb(240, 200, 40);
+    pub const SAND: Rgb = Rgb(210, 180, 110);
+
+    /// Goes from `self` at 0 to `other` at 1.
+    pub fn mix(self, other: Rgb, t: f64) -> Rgb {
+        let t = t.clamp(0.0, 1.0);
+        let channel = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * t).round() as u8;
+        Rgb(
+            channel(self.0, other.0),
+            channel(self.1, other.1),
+            channel(self.2, other.2),
+        )
+    }
+}
+
+/// A grid of colored cells, row by row from the top.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    width: usize,
+    height: usize,
+    cells: Vec<Rgb>,
+}
+
+impl Frame {
+    pub fn new(width: usize, height: usize, background: Rgb) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![background; width * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, [x, y]: [usize; 2]) -> Option<Rgb> {
+        (x < self.width && y < self.height).then(|| self.cells[x + y * self.width])
+    }
+
+    /// Cells outside the frame are ignored.
+    pub fn set(&mut self, [x, y]: [usize; 2], color: Rgb) {
+        if x < self.width && y < self.height {
+            self.cells[x + y * self.width] = color;
+        }
+    }
+}
+
+/// A simulation state that can be drawn.
+pub trait Visualize {
+    fn frame(&self) -> Frame;
+}
+
+pub trait FrameSink {
+    fn add_frame(&mut self, frame: &Frame) -> io::Result<()>;
+
+    fn finish(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// A binary PPM image, with each cell drawn as a square of `scale` pixels.
+pub fn write_ppm(frame: &Frame, scale: usize, out: &mut impl Write) -> io::Result<()> {
+    write!(
+        out,
+        "P6\n{} {}\n255\n",
+        frame.width * scale,
+        frame.height * scale
+    )?;
+    let mut row = Vec::with_capacity(frame.width * scale * 3);
+    for cells in frame.cells.chunks(frame.width.max(1)) {
+        row.clear();
+        for Rgb(r, g, b) in cells {
+            for _ in 0..scale {
+                row.extend([r, g, b]);
+            }
+        }
+        for _ in 0..scale {
+            out.write_all(&row)?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes the frames to `frame_00000.ppm`, `frame_00001.ppm`, ... in a directory.
+pub struct PpmSequence {
+    dir: PathBuf,
+    scale: usize,
+    num_frames: usize,
+}
+
+impl PpmSequence {
+    pub fn new(dir: impl Into<PathBuf>, scale: usize) -> io::Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self {
+            dir,
+            scale,
+            num_frames: 0,
+        })
+    }
+
+    pub fn num_frames(&self) -> usize {
+        self.num_frames
+    }
+}
+
+impl FrameSink for PpmSequence {
+    fn add_frame(&mut self, frame: &Frame) -> io::Result<()> {
+        let path = self.dir.join(format!("frame_{:05}.ppm", self.num_frames));
+        let mut file = io::BufWriter::new(std::fs::File::create(path)?);
+        write_ppm(frame, self.scale, &mut file)?;
+        file.flush()?;
+        self.num_frames += 1;
+        Ok(())
+    }
+}
+
+/// Two rows of cells per line of text, using the upper half block with 24-bit colors.
+pub fn ansi_lines(frame: &Frame) -> String {
+    let mut result = String::new();
+    for y in (0..frame.height).step_by(2) {
+        for x in 0..frame.width {
+            let Rgb(r, g, b) = frame.get([x, y]).unwrap();
+            write!(result, "\x1b[38;2;{};{};{}m", r, g, b).unwrap();
+            match frame.get([x, y + 1]) {
+                Some(Rgb(r, g, b)) => write!(result, "\x1b[48;2;{};{};{}m", r, g, b).unwrap(),
+                None => result.push_str("\x1b[49m"),
+            }
+            result.push('▀');
+        }
+        result.push_str("\x1b[0m\n");
+    }
+    result
+}
+
+/// Plays the frames in a terminal, waiting `delay` after each one.
+pub struct AnsiPlayer<W: Write> {
+    out: W,
+    delay: Duration,
+    size: Option<[usize; 2]>,
+}
+
+impl<W: Write> AnsiPlayer<W> {
+    pub fn new(out: W, delay: Duration) -> Self {
+        Self {
+            out,
+            delay,
+            size: None,
+        }
+    }
+}
+
+impl<W: Write> FrameSink for AnsiPlayer<W> {
+    fn add_frame(&mut self, frame: &Frame) -> io::Result<()> {
+        let size = [frame.width, frame.height];
+        if self.size != Some(size) {
+            // Hide the cursor and clear what is left of a larger frame.
+            write!(self.out, "\x1b[?25l\x1b[2J")?;
+            self.size = Some(size);
+        }
+        write!(self.out, "\x1b[H{}", ansi_lines(frame))?;
+        self.out.flush()?;
+        std::thread::sleep(self.delay);
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        write!(self.out, "\x1b[0m\x1b[?25h")?;
+        self.out.flush()
+    }
+}
+
+/// Parses `data` like [`crate::solution::Solution::parse`] does, and hands it to `simulate`.
+pub fn observe_parsed<T>(
+    data: &str,
+    generator: fn(&str) -> Result<T, ParseError>,
+    simulate: impl FnOnce(&T),
+) -> Result<(), ParseError> {
+    simulate(&generator(&normalize(data).0)?);
+    Ok(())
+}
+
+type Simulation = fn(&str, Part, &mut dyn FnMut(&dyn Visualize)) -> Result<(), ParseError>;
+
+/// The days with a visualization, and how to run them.
+static SIMULATIONS: &[(u8, Simulation)] = &[
+    #[cfg(feature = "day12")]
+    (12, |data, part, observe| {
+        observe_parsed(data, crate::day12::generator, |input| {
+            crate::day12::visualize(input, part, observe)
+        })
+    }),
+    #[cfg(feature = "day14")]
+    (14, |data, part, observe| {
+        observe_parsed(data, crate::day14::generator, |input| {
+            crate::day14::visualize(input, part, observe)
+        })
+    }),
+    #[cfg(feature = "day17")]
+    (17, |data, part, observe| {
+        observe_parsed(data, crate::day17::generator, |input| {
+            crate::day17::visualize(input, part, observe)
+        })
+    }),
+    #[cfg(feature = "day22")]
+    (22, |data, part, observe| {
+        observe_parsed(data, crate::day22::generator, |input| {
+            crate::day22::visualize(input, part, observe)
+        })
+    }),
+    #[cfg(feature = "day23")]
+    (23, |data, part, observe| {
+        observe_parsed(data, crate::day23::generator, |input| {
+            crate::day23::visualize(input, part, observe)
+        })
+    }),
+    #[cfg(feature = "day24")]
+    (24, |data, part, observe| {
+        observe_parsed(data, crate::day24::generator, |input| {
+            crate::day24::visualize(input, part, observe)
+        })
+    }),
+];
+
+/// Runs the simulation of `day`'s `part` on `data`, calling `observe` with its state after each
+/// step. Returns `None` for days without a visualization.
+pub fn simulate(
+    day: u8,
+    data: &str,
+    part: Part,
+    observe: &mut dyn FnMut(&dyn Visualize),
+) -> Option<Result<(), ParseError>> {
+    let (_, simulation) = SIMULATIONS.iter().find(|(d, _)| *d == day)?;
+    Some(simulation(data, part, observe))
+}
+
+/// Draws every `every`th step of the simulation into `sink`, and returns the number of frames.
+/// Returns `None` for days without a visualization.
+pub fn record(
+    day: u8,
+    data: &str,
+    part: Part,
+    every: usize,
+    sink: &mut dyn FrameSink,
+) -> Option<io::Result<usize>> {
+    let every = every.max(1);
+    let mut step = 0;
+    let mut num_frames = 0;
+    let mut result = Ok(());
+    let parsed = simulate(day, data, part, &mut |state| {
+        if result.is_ok() && step % every == 0 {
+            result = sink.add_frame(&state.frame());
+            num_frames += 1;
+        }
+        step += 1;
+    })?;
+    Some(
+        parsed
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+            .and(result)
+            .and_then(|()| sink.finish())
+            .map(|()| num_frames),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checkerboard() -> Frame {
+        let mut frame = Frame::new(2, 3, Rgb::BLACK);
+        frame.set([0, 0], Rgb::WHITE);
+        frame.set([1, 1], Rgb::WHITE);
+        frame.set([0, 2], Rgb::WHITE);
+        frame.set([5, 5], Rgb::RED);
+        frame
+    }
+
+    #[test]
+    fn ppm_image() {
+        let mut out = Vec::new();
+        write_ppm(&checkerboard(), 2, &mut out).unwrap();
+        let header = b"P6\n4 6\n255\n";
+        assert_eq!(&out[..header.len()], header);
+        let pixels = &out[header.len()..];
+        assert_eq!(pixels.len(), 4 * 6 * 3);
+        let white_pixels = [0, 1, 4, 5, 10, 11, 14, 15, 16, 17, 20, 21];
+        for (i, pixel) in pixels.chunks(3).enumerate() {
+            let expected = if white_pixels.contains(&i) { 255 } else { 0 };
+            assert_eq!(pixel, [expected; 3], "pixel {}", i);
+        }
+    }
+
+    #[test]
+    fn ansi_half_blocks() {
+        let lines = ansi_lines(&checkerboard());
+        assert_eq!(lines.lines().count(), 2);
+        assert_eq!(lines.matches('▀').count(), 4);
+        assert!(lines.starts_with("\x1b[38;2;255;255;255m\x1b[48;2;0;0;0m▀"));
+        assert!(lines.ends_with("\x1b[38;2;0;0;0m\x1b[49m▀\x1b[0m\n"));
+    }
+
+    #[test]
+    fn mix_colors() {
+        assert_eq!(Rgb::BLACK.mix(Rgb::WHITE, 0.5), Rgb(128, 128, 128));
+        assert_eq!(Rgb::BLACK.mix(Rgb::RED, 2.0), Rgb::RED);
+    }
+
+    #[derive(Default)]
+    struct Collect(Vec<Frame>);
+
+    impl FrameSink for Collect {
+        fn add_frame(&mut self, frame: &Frame) -> io::Result<()> {
+            self.0.push(frame.clone());
+            Ok(())
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "day14")]
+    fn record_frames() {
+        let data = "498,4 -> 498,6 -> 496,6\n503,4 -> 502,4 -> 502,9 -> 494,9\n";
+        let mut frames = Collect::default();
+        // One frame before the first unit of sand, and one after each of the 24 that settle.
+        assert_eq!(
+            record(14, data, Part::One, 1, &mut frames)
+                .unwrap()
+                .unwrap(),
+            25
+        );
+        assert_eq!(frames.0.len(), 25);
+        let mut every_10th = Collect::default();
+        assert_eq!(
+            record(14, data, Part::One, 10, &mut every_10th)
+                .unwrap()
+                .unwrap(),
+            3
+        );
+        assert_eq!(every_10th.0[1], frames.0[10]);
+
+        assert!(record(1, "1\n", Part::One, 1, &mut Collect::default()).is_none());
+        let err = record(14, "x\n", Part::One, 1, &mut Collect::default()).unwrap();
+        assert_eq!(err.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    const DAY22_EXAMPLE: &str = "        ...#
+        .#..
+        #...
+        ....
+...#.......#
+........#...
+..#....#....
+..........#.
+        ...#....
+        .....#..
+        .#......
+        ......#.
+
+10R5L5R10L4R5L5
+";
+
+    #[test]
+    fn every_day_draws_frames() {
+        let examples = [
+            (12, "Sabqponm\nabcryxxl\naccszExk\nacctuvwj\nabdefghi\n"),
+            (
+                14,
+                "498,4 -> 498,6 -> 496,6\n503,4 -> 502,4 -> 502,9 -> 494,9\n",
+            ),
+            (17, ">>><<><>><<<>><>>><<<>>><<<><<<>><>><<>>\n"),
+            (22, DAY22_EXAMPLE),
+            (
+                23,
+                "....#..\n..###.#\n#...#.#\n.#...##\n#.###..\n##.#.##\n.#..#..\n",
+            ),
+            (
+                24,
+                "#.######\n#>>.<^<#\n#.<..<<#\n#>v.><>#\n#<^v^^>#\n######.#\n",
+            ),
+        ];
+        for &(day, _) in SIMULATIONS {
+            let (_, data) = examples
+                .iter()
+                .find(|(example_day, _)| *example_day == day)
+                .unwrap_or_else(|| panic!("no example for day {}", day));
+            for part in Part::ALL {
+                let mut frames = Collect::default();
+                record(day, data, part, 1, &mut frames).unwrap().unwrap();
+                assert!(1 < frames.0.len(), "day {} {}", day, part.name());
+                let size = |frame: &Frame| [frame.width(), frame.height()];
+                assert!(size(&frames.0[0]).iter().all(|&side| 0 < side));
+            }
+        }
+    }
+}
@@ -0,0 +1,46 @@
+// The tests run day 1, which only exists with its feature.
+#![cfg(feature = "day1")]
+
+use std::process::Command;
+
+fn run(args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_aoc-2022"))
+        .args(args)
+        .output()
+        .expect("failed to run the binary")
+}
+
+#[test]
+fn missing_input_file() {
+    for mode in ["--parallel", "--json"] {
+        let output = run(&[mode, "-d", "1", "-f", "/nonexistent/day1.txt"]);
+        assert!(!output.status.success(), "{} succeeded", mode);
+        let printed = [output.stdout, output.stderr].concat();
+        let printed = String::from_utf8_lossy(&printed);
+        assert!(
+            printed.contains("failed to read /nonexistent/day1.txt"),
+            "{} printed {:?}",
+            mode,
+            printed
+        );
+    }
+}
+
+#[test]
+fn missing_input_file_in_json() {
+    let output = run(&["--json", "-d", "1", "-f", "/nonexistent/day1.txt"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains(r#""kind": "input""#), "{}", stdout);
+}
+
+#[test]
+fn missing_batch_directory() {
+    let output = run(&["--batch", "/nonexistent", "-d", "1"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("failed to read inputs from /nonexistent"),
+        "{}",
+        stderr
+    );
+}